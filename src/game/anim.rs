@@ -14,6 +14,7 @@ impl Animation {
         }
     }
 
+    #[allow(dead_code)]
     pub fn reset(&mut self) {
         self.current_frame = 0;
     }
@@ -0,0 +1,38 @@
+use super::chunk::SEA_LEVEL;
+
+/// Surface above this row counts as mountains.
+pub const MOUNTAINS_HEIGHT: usize = 180;
+
+/// Surface within this many rows above sea level counts as a beach.
+pub const BEACH_HEIGHT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Biome {
+    Ocean,
+    Beach,
+    Plains,
+    Mountains,
+}
+
+impl Biome {
+    pub fn from_height(height: usize) -> Self {
+        if height < SEA_LEVEL {
+            Self::Ocean
+        } else if height <= SEA_LEVEL + BEACH_HEIGHT {
+            Self::Beach
+        } else if height < MOUNTAINS_HEIGHT {
+            Self::Plains
+        } else {
+            Self::Mountains
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ocean => "ocean",
+            Self::Beach => "beach",
+            Self::Plains => "plains",
+            Self::Mountains => "mountains",
+        }
+    }
+}
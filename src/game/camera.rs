@@ -0,0 +1,465 @@
+use std::ops::RangeInclusive;
+
+use crossterm::event::KeyCode;
+use rand::Rng;
+
+use crate::util::{Coord, CoordReal, Dim, DimReal};
+
+use super::{
+    chunk::CHUNK_HEIGHT,
+    console::{parse_arg, Command, Console},
+    physics::TIMESTEP,
+    renderer::{Camera, Screen},
+    Game,
+};
+
+/// Free camera speed in tiles per second.
+pub const FREE_CAMERA_SPEED: DimReal = 30.0;
+pub const FREE_CAMERA_MIN_SPEED: DimReal = 5.0;
+pub const FREE_CAMERA_MAX_SPEED: DimReal = 480.0;
+
+/// Rows of sky the camera may show above the top of the world.
+pub const CAMERA_TOP_MARGIN: Dim = 16;
+
+/// Zoom levels cycled through by the map key. See `Camera::zoom`.
+pub const MAP_ZOOM_LEVELS: [Dim; 3] = [1, 2, 4];
+
+/// Offset in tiles at full trauma.
+pub const MAX_SHAKE_OFFSET: DimReal = 3.0;
+/// Trauma lost per second.
+pub const SHAKE_DECAY: DimReal = 1.5;
+/// Trauma per point of fall damage taken by the player.
+pub const LANDING_SHAKE: DimReal = 0.02;
+
+/// Tuning for `CameraController`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraConfig {
+    /// Half the size of the box around the focus the target can move in
+    /// without moving the camera.
+    pub deadzone: CoordReal,
+    /// How quickly the camera catches up, as a rate per second. Higher is snappier.
+    pub smoothing: DimReal,
+    /// Seconds of horizontal velocity to look ahead by.
+    pub look_ahead_time: DimReal,
+    pub max_look_ahead: DimReal,
+    /// How quickly the look-ahead follows changes in velocity, as a rate per second.
+    pub look_ahead_smoothing: DimReal,
+    /// Rows to frame the camera below the target, to show more of the ground.
+    pub ground_bias: DimReal,
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        Self {
+            deadzone: CoordReal { row: 2.0, col: 2.0 },
+            smoothing: 6.0,
+            look_ahead_time: 0.5,
+            max_look_ahead: 6.0,
+            look_ahead_smoothing: 2.0,
+            ground_bias: 4.0,
+        }
+    }
+}
+
+/// Smoothly follows a moving target. Works in world space only, so it
+/// doesn't need a screen.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CameraController {
+    pub config: CameraConfig,
+    /// Trails the target, only moving once the target leaves the deadzone.
+    anchor: CoordReal,
+    focus: CoordReal,
+    look_ahead: DimReal,
+}
+
+impl CameraController {
+    /// Focus as a tile, for `Camera::position`.
+    pub fn tile_focus(&self) -> Coord {
+        Coord {
+            row: self.focus.row.round() as Dim,
+            col: self.focus.col.round() as Dim,
+        }
+    }
+
+    /// Point the camera is heading for.
+    fn framing(&self) -> CoordReal {
+        CoordReal {
+            row: self.anchor.row - self.config.ground_bias,
+            col: self.anchor.col + self.look_ahead,
+        }
+    }
+
+    /// Jump straight to the target, dropping any look-ahead.
+    pub fn snap(&mut self, target: CoordReal) {
+        self.look_ahead = 0.0;
+        self.anchor = target;
+        self.focus = self.framing();
+    }
+
+    /// Move the focus towards a target moving at `velocity` over `dt` seconds.
+    pub fn update(&mut self, target: CoordReal, velocity: CoordReal, dt: DimReal) {
+        let config = &self.config;
+
+        let max_look_ahead = config.max_look_ahead;
+        let look_ahead_goal =
+            (velocity.col * config.look_ahead_time).clamp(-max_look_ahead, max_look_ahead);
+
+        self.look_ahead +=
+            (look_ahead_goal - self.look_ahead) * smoothing_factor(config.look_ahead_smoothing, dt);
+
+        let deadzone = self.config.deadzone;
+
+        self.anchor = CoordReal {
+            row: clamp_to_deadzone(self.anchor.row, target.row, deadzone.row),
+            col: clamp_to_deadzone(self.anchor.col, target.col, deadzone.col),
+        };
+
+        let goal = self.framing();
+        self.focus += (goal - self.focus) * smoothing_factor(self.config.smoothing, dt);
+    }
+}
+
+/// Fraction of the remaining distance covered in `dt` by exponential smoothing.
+fn smoothing_factor(rate: DimReal, dt: DimReal) -> DimReal {
+    1.0 - (-rate * dt).exp()
+}
+
+/// Closest position to `anchor` that keeps `target` within `deadzone` of it.
+fn clamp_to_deadzone(anchor: DimReal, target: DimReal, deadzone: DimReal) -> DimReal {
+    anchor.clamp(target - deadzone, target + deadzone)
+}
+
+/// Jitters the camera after impacts. Trauma from 0.0 to 1.0 builds up with
+/// each impact and wears off over time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CameraShake {
+    trauma: DimReal,
+    /// Applied to the camera on the last update, to be undone on the next.
+    offset: Coord,
+}
+
+impl CameraShake {
+    pub fn add_trauma(&mut self, amount: DimReal) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    /// Pick a new random offset for this tick. Squaring the trauma keeps
+    /// small knocks subtle.
+    fn update(&mut self, dt: DimReal) {
+        let magnitude = MAX_SHAKE_OFFSET * self.trauma * self.trauma;
+        self.trauma = (self.trauma - SHAKE_DECAY * dt).max(0.0);
+
+        let mut rng = rand::thread_rng();
+        let mut offset = || (rng.gen_range(-1.0..=1.0) * magnitude).round() as Dim;
+
+        self.offset = Coord {
+            row: offset(),
+            col: offset(),
+        };
+    }
+}
+
+impl Camera {
+    /// Rows the camera can be centered on without showing anything below row 0
+    /// or more than `CAMERA_TOP_MARGIN` rows above the world.
+    pub fn row_bounds(&self, screen: &Screen) -> RangeInclusive<Dim> {
+        let below = screen.rows() / 2 * self.zoom;
+        let above = (screen.rows() - 1 - screen.rows() / 2) * self.zoom;
+
+        let top = CHUNK_HEIGHT as Dim + CAMERA_TOP_MARGIN;
+
+        if below + above > top {
+            // Taller than the world: keep it centered.
+            let middle = top / 2;
+            middle..=middle
+        } else {
+            below..=top - above
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraMode {
+    FollowPlayer,
+    /// Detached from the player and moved with the movement keys.
+    Free {
+        position: CoordReal,
+        speed: DimReal,
+    },
+}
+
+impl Game {
+    /// Switch between following the player and flying around freely.
+    pub fn toggle_free_camera(&mut self) {
+        self.camera_mode = match self.camera_mode {
+            CameraMode::FollowPlayer => CameraMode::Free {
+                position: self.camera.position.to_real(),
+                speed: FREE_CAMERA_SPEED,
+            },
+            CameraMode::Free { .. } => CameraMode::FollowPlayer,
+        };
+
+        // Keys held down for the player shouldn't carry over to the camera and back.
+        self.held_keys.clear();
+    }
+
+    pub fn is_camera_free(&self) -> bool {
+        matches!(self.camera_mode, CameraMode::Free { .. })
+    }
+
+    /// Step through `MAP_ZOOM_LEVELS`.
+    pub fn cycle_map_zoom(&mut self) {
+        let current = MAP_ZOOM_LEVELS
+            .iter()
+            .position(|&zoom| zoom == self.camera.zoom);
+        let next = current.map_or(0, |idx| (idx + 1) % MAP_ZOOM_LEVELS.len());
+
+        self.camera.zoom = MAP_ZOOM_LEVELS[next];
+    }
+
+    pub fn shake_camera(&mut self, trauma: DimReal) {
+        self.camera_shake.add_trauma(trauma);
+    }
+
+    pub fn update_camera(&mut self, screen: &Screen) {
+        // Modes that don't move the camera this tick would otherwise keep
+        // last tick's shake.
+        self.camera.position = self.camera.position - self.camera_shake.offset;
+
+        match self.camera_mode {
+            CameraMode::FollowPlayer => self.follow_player(),
+            CameraMode::Free { position, speed } => self.fly_camera(position, speed),
+        }
+
+        let bounds = self.camera.row_bounds(screen);
+        let clamp_row = |position: Coord| Coord {
+            row: position.row.clamp(*bounds.start(), *bounds.end()),
+            ..position
+        };
+
+        let base = clamp_row(self.camera.position);
+
+        self.camera_shake.update(TIMESTEP);
+        self.camera.position = clamp_row(base + self.camera_shake.offset);
+
+        // Only undo the part of the shake that survived the bounds.
+        self.camera_shake.offset = self.camera.position - base;
+
+        // Don't let the free camera drift off where it can't be seen moving.
+        if let CameraMode::Free { position, .. } = &mut self.camera_mode {
+            position.row = position
+                .row
+                .clamp(*bounds.start() as DimReal, *bounds.end() as DimReal);
+        }
+    }
+
+    fn follow_player(&mut self) {
+        let Some(body) = self.get_player_body() else {
+            return;
+        };
+
+        let (position, velocity) = (body.position, body.velocity);
+
+        self.camera_controller.update(position, velocity, TIMESTEP);
+        self.camera.position = self.camera_controller.tile_focus();
+    }
+
+    fn fly_camera(&mut self, mut position: CoordReal, mut speed: DimReal) {
+        if self.is_key_pressed(KeyCode::Char('='), false) {
+            speed = (speed * 2.0).min(FREE_CAMERA_MAX_SPEED);
+        }
+
+        if self.is_key_pressed(KeyCode::Char('-'), false) {
+            speed = (speed / 2.0).max(FREE_CAMERA_MIN_SPEED);
+        }
+
+        let axis = |positive, negative| {
+            let held = |key| self.is_key_held(KeyCode::Char(key), false);
+            (held(positive) as i8 - held(negative) as i8) as DimReal
+        };
+
+        let direction = CoordReal {
+            row: axis('w', 's'),
+            col: axis('d', 'a'),
+        };
+
+        position += direction * (speed * TIMESTEP);
+
+        self.camera.position = Coord {
+            row: position.row.round() as Dim,
+            col: position.col.round() as Dim,
+        };
+
+        self.camera_mode = CameraMode::Free { position, speed };
+    }
+}
+
+impl CameraConfig {
+    pub const SETTINGS: [&'static str; 6] = [
+        "deadzone_cols",
+        "deadzone_rows",
+        "smoothing",
+        "look_ahead",
+        "max_look_ahead",
+        "ground_bias",
+    ];
+
+    /// Reject values that would make the camera diverge, freeze or panic
+    /// while clamping.
+    fn validate_setting(name: &str, value: DimReal) -> Result<(), String> {
+        match name {
+            _ if !value.is_finite() => Err(format!("{name} must be a finite number")),
+            "smoothing" if value <= 0.0 => Err(format!("{name} must be positive")),
+            "ground_bias" => Ok(()),
+            _ if value < 0.0 => Err(format!("{name} can't be negative")),
+            _ => Ok(()),
+        }
+    }
+
+    fn setting_mut(&mut self, name: &str) -> Option<&mut DimReal> {
+        match name {
+            "deadzone_cols" => Some(&mut self.deadzone.col),
+            "deadzone_rows" => Some(&mut self.deadzone.row),
+            "smoothing" => Some(&mut self.smoothing),
+            "look_ahead" => Some(&mut self.look_ahead_time),
+            "max_look_ahead" => Some(&mut self.max_look_ahead),
+            "ground_bias" => Some(&mut self.ground_bias),
+            _ => None,
+        }
+    }
+}
+
+pub fn register_commands(console: &mut Console) {
+    console.register(Command {
+        name: "camera",
+        usage: "camera <setting> [value]",
+        run: |game, args| {
+            let name = args.first().ok_or("Missing <setting>")?;
+
+            let setting = game
+                .camera_controller
+                .config
+                .setting_mut(name)
+                .ok_or_else(|| format!("Unknown setting `{name}`"))?;
+
+            if args.len() > 1 {
+                let value = parse_arg(args, 1, "value")?;
+                CameraConfig::validate_setting(name, value)?;
+
+                *setting = value;
+            }
+
+            Ok(format!("{name}: {setting}"))
+        },
+        complete: |idx| match idx {
+            0 => CameraConfig::SETTINGS.map(String::from).to_vec(),
+            _ => vec![],
+        },
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: DimReal = 0.01;
+
+    fn controller_at(target: CoordReal) -> CameraController {
+        let mut controller = CameraController::default();
+        controller.snap(target);
+        controller
+    }
+
+    fn run(
+        controller: &mut CameraController,
+        target: CoordReal,
+        velocity: CoordReal,
+        ticks: usize,
+    ) {
+        for _ in 0..ticks {
+            controller.update(target, velocity, TIMESTEP);
+        }
+    }
+
+    #[test]
+    fn anchor_stays_while_target_is_inside_deadzone() {
+        let start = CoordReal {
+            row: 100.0,
+            col: 0.0,
+        };
+        let mut controller = controller_at(start);
+
+        let deadzone = controller.config.deadzone;
+        let target = CoordReal {
+            row: start.row + deadzone.row * 0.5,
+            col: start.col - deadzone.col * 0.9,
+        };
+
+        run(&mut controller, target, CoordReal::ZERO, 120);
+
+        assert_eq!(controller.anchor, start);
+    }
+
+    #[test]
+    fn update_converges_on_framing() {
+        let mut controller = controller_at(CoordReal::ZERO);
+        let target = CoordReal {
+            row: 30.0,
+            col: 50.0,
+        };
+
+        run(&mut controller, target, CoordReal::ZERO, 600);
+
+        assert!((controller.focus - controller.framing()).length() < EPSILON);
+    }
+
+    #[test]
+    fn look_ahead_is_clamped() {
+        let mut controller = controller_at(CoordReal::ZERO);
+        let max_look_ahead = controller.config.max_look_ahead;
+
+        let velocity = CoordReal {
+            row: 0.0,
+            col: 1000.0,
+        };
+
+        run(&mut controller, CoordReal::ZERO, velocity, 600);
+        assert!((controller.look_ahead - max_look_ahead).abs() < EPSILON);
+
+        run(&mut controller, CoordReal::ZERO, velocity * -1.0, 600);
+        assert!((controller.look_ahead + max_look_ahead).abs() < EPSILON);
+    }
+
+    #[test]
+    fn ground_bias_frames_below_target() {
+        let mut controller = controller_at(CoordReal::ZERO);
+        // Without a deadzone the anchor sits on the target, so only the bias
+        // separates the two.
+        controller.config.deadzone = CoordReal::ZERO;
+
+        let ground_bias = controller.config.ground_bias;
+        assert!(ground_bias > 0.0);
+
+        let target = CoordReal {
+            row: 100.0,
+            col: 0.0,
+        };
+        let expected = target.row - ground_bias;
+
+        run(&mut controller, target, CoordReal::ZERO, 1);
+        assert!(controller.focus.row < expected - 1.0);
+
+        run(&mut controller, target, CoordReal::ZERO, 600);
+        assert!((controller.focus.row - expected).abs() < EPSILON);
+    }
+
+    #[test]
+    fn rejects_settings_that_break_the_camera() {
+        assert!(CameraConfig::validate_setting("smoothing", -1.0).is_err());
+        assert!(CameraConfig::validate_setting("smoothing", 0.0).is_err());
+        assert!(CameraConfig::validate_setting("deadzone_cols", -1.0).is_err());
+        assert!(CameraConfig::validate_setting("max_look_ahead", DimReal::NAN).is_err());
+        assert!(CameraConfig::validate_setting("ground_bias", -4.0).is_ok());
+        assert!(CameraConfig::validate_setting("deadzone_rows", 0.0).is_ok());
+    }
+}
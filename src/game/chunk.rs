@@ -4,7 +4,7 @@ use crossterm::style::Color;
 use noise::{NoiseFn, OpenSimplex};
 use serde::{Deserialize, Serialize};
 
-use crate::util::{Coord, Dim};
+use crate::util::{Coord, Dim, DimReal};
 
 pub const CHUNK_WIDTH: usize = 16;
 pub const CHUNK_HEIGHT: usize = 256;
@@ -12,8 +12,16 @@ pub const CHUNK_HEIGHT: usize = 256;
 pub const TERRAIN_HEIGHT: usize = 196;
 pub const TERRAIN_BASE: usize = 128;
 
+/// Columns whose surface lies below this row are flooded with water up to it.
+pub const SEA_LEVEL: usize = 150;
+
 pub const TERRAIN_SCALE: f64 = 0.5;
 
+/// Ore veins come from a separate slice of the terrain noise.
+pub const ORE_SCALE: f64 = 0.3;
+pub const ORE_NOISE_OFFSET: f64 = 1000.0;
+pub const ORE_THRESHOLD: f64 = 0.6;
+
 #[derive(Serialize, Deserialize)]
 pub struct Chunk {
     world_position: Dim,
@@ -31,23 +39,30 @@ impl Chunk {
     pub fn regenerate(&mut self, noise: &OpenSimplex) {
         for col in 0..CHUNK_WIDTH {
             let world_position = self.world_position() + col as Dim;
-            let chunk_position = world_position as f64 / CHUNK_WIDTH as f64;
-            let noise_position = chunk_position / TERRAIN_SCALE;
-
-            let noise_value = noise.get([noise_position, 0.0]);
-
-            let slope_scale = noise_value * 0.5 + 0.5;
-            let slope_height = TERRAIN_HEIGHT - TERRAIN_BASE;
+            let height = generated_height(noise, world_position);
 
-            let height = TERRAIN_BASE + (slope_scale * slope_height as f64) as usize;
-
-            let mut layers = [
+            let mut land_layers = [
                 (1, Tile::Grass),
                 (1, Tile::GrassyDirt),
                 (8, Tile::Dirt),
                 (CHUNK_HEIGHT, Tile::Stone),
             ];
 
+            let mut seabed_layers = [
+                (3, Tile::Sand),
+                (2, Tile::Gravel),
+                (5, Tile::Dirt),
+                (CHUNK_HEIGHT, Tile::Stone),
+            ];
+
+            let layers = {
+                if height < SEA_LEVEL {
+                    &mut seabed_layers[..]
+                } else {
+                    &mut land_layers[..]
+                }
+            };
+
             let mut layer_idx = 0;
 
             for row in (0..CHUNK_HEIGHT).rev() {
@@ -66,7 +81,13 @@ impl Chunk {
                             layer_idx += 1;
                         }
 
-                        Some(layer.1)
+                        if layer.1 == Tile::Stone && is_ore_vein(noise, coord) {
+                            Some(Tile::IronOre)
+                        } else {
+                            Some(layer.1)
+                        }
+                    } else if row <= SEA_LEVEL {
+                        Some(Tile::Water)
                     } else {
                         None
                     }
@@ -90,6 +111,11 @@ impl Chunk {
         Some(index as usize)
     }
 
+    /// Whether the world coordinate lies inside this chunk.
+    pub fn contains(&self, index: Coord) -> bool {
+        self.flat_index(index).is_some()
+    }
+
     pub fn world_position(&self) -> Dim {
         self.world_position
     }
@@ -99,6 +125,29 @@ impl Chunk {
     }
 }
 
+/// Row of the topmost generated tile in the given world column.
+pub fn generated_height(noise: &OpenSimplex, world_col: Dim) -> usize {
+    let chunk_position = world_col as f64 / CHUNK_WIDTH as f64;
+    let noise_position = chunk_position / TERRAIN_SCALE;
+
+    let noise_value = noise.get([noise_position, 0.0]);
+
+    let slope_scale = noise_value * 0.5 + 0.5;
+    let slope_height = TERRAIN_HEIGHT - TERRAIN_BASE;
+
+    TERRAIN_BASE + (slope_scale * slope_height as f64) as usize
+}
+
+/// Whether the stone at the given position is replaced with ore.
+fn is_ore_vein(noise: &OpenSimplex, pos: Coord) -> bool {
+    let point = [
+        pos.col as f64 * ORE_SCALE,
+        pos.row as f64 * ORE_SCALE + ORE_NOISE_OFFSET,
+    ];
+
+    noise.get(point) > ORE_THRESHOLD
+}
+
 /// Uses world coordinates for indexing.
 impl Index<Coord> for Chunk {
     type Output = Option<Tile>;
@@ -123,22 +172,174 @@ impl IndexMut<Coord> for Chunk {
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Tile {
     Dirt,
     Stone,
     GrassyDirt,
     Grass,
     Flower(Color),
+    Ladder,
+    Vine,
+    /// One-way platform: can be jumped through from below and dropped through.
+    Platform,
+    Water,
+    Lava,
+    Sand,
+    Gravel,
+    /// Touching it moves the player's respawn point here.
+    Checkpoint,
+    /// Explodes when caught in another explosion or hit by a bomb.
+    Tnt,
+    /// Crafting station for most recipes.
+    Workbench,
+    /// Found in stone; needs a stone pickaxe or better.
+    IronOre,
 }
 
 impl Tile {
+    /// Tiles that can be referred to by id. Flowers carry a color and are left out.
+    pub const NAMED: [Self; 15] = [
+        Self::Dirt,
+        Self::Stone,
+        Self::GrassyDirt,
+        Self::Grass,
+        Self::Ladder,
+        Self::Vine,
+        Self::Platform,
+        Self::Water,
+        Self::Lava,
+        Self::Sand,
+        Self::Gravel,
+        Self::Checkpoint,
+        Self::Tnt,
+        Self::Workbench,
+        Self::IronOre,
+    ];
+
     pub fn is_impassable(&self) -> bool {
         use Tile::*;
 
+        !matches!(
+            self,
+            Grass | Flower(_) | Ladder | Vine | Platform | Water | Lava | Checkpoint
+        )
+    }
+
+    /// Whether the tile turns into a falling block when left without support.
+    pub fn falls(&self) -> bool {
+        matches!(self, Tile::Sand | Tile::Gravel)
+    }
+
+    pub fn fluid(&self) -> Option<Fluid> {
         match self {
-            Grass | Flower(_) => false,
-            _ => true,
+            Tile::Water => Some(Fluid {
+                buoyancy: 1.1,
+                drag: 3.0,
+                contact_damage: 0.0,
+            }),
+            Tile::Lava => Some(Fluid {
+                buoyancy: 0.9,
+                drag: 8.0,
+                contact_damage: 10.0,
+            }),
+            _ => None,
         }
     }
+
+    pub fn is_climbable(&self) -> bool {
+        matches!(self, Tile::Ladder | Tile::Vine)
+    }
+
+    pub fn is_one_way(&self) -> bool {
+        matches!(self, Tile::Platform)
+    }
+
+    /// Identifier used in data files.
+    pub fn id(&self) -> &'static str {
+        use Tile::*;
+
+        match self {
+            Dirt => "dirt",
+            Stone => "stone",
+            GrassyDirt => "grassy_dirt",
+            Grass => "grass",
+            Flower(_) => "flower",
+            Ladder => "ladder",
+            Vine => "vine",
+            Platform => "platform",
+            Water => "water",
+            Lava => "lava",
+            Sand => "sand",
+            Gravel => "gravel",
+            Checkpoint => "checkpoint",
+            Tnt => "tnt",
+            Workbench => "workbench",
+            IronOre => "iron_ore",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::NAMED.into_iter().find(|tile| tile.id() == id)
+    }
+
+    pub fn name(&self) -> &'static str {
+        use Tile::*;
+
+        match self {
+            Dirt => "Dirt",
+            Stone => "Stone",
+            GrassyDirt => "Grassy dirt",
+            Grass => "Grass",
+            Flower(_) => "Flower",
+            Ladder => "Ladder",
+            Vine => "Vine",
+            Platform => "Platform",
+            Water => "Water",
+            Lava => "Lava",
+            Sand => "Sand",
+            Gravel => "Gravel",
+            Checkpoint => "Checkpoint",
+            Tnt => "TNT",
+            Workbench => "Workbench",
+            IronOre => "Iron ore",
+        }
+    }
+
+    /// Resistance to being broken by collisions, explosions and mining.
+    pub fn durability(&self) -> DimReal {
+        use Tile::*;
+
+        match self {
+            Grass | Flower(_) | Vine => 0.1,
+            Ladder | Platform | Tnt => 0.5,
+            Sand => 0.8,
+            Dirt | GrassyDirt | Gravel => 1.0,
+            Checkpoint | Workbench => 2.0,
+            Stone => 3.0,
+            IronOre => 4.0,
+            Water | Lava => DimReal::INFINITY,
+        }
+    }
+
+    /// Multiplier for the walking deceleration of entities standing on the tile.
+    pub fn friction(&self) -> DimReal {
+        use Tile::*;
+
+        match self {
+            Stone => 0.8,
+            _ => 1.0,
+        }
+    }
+}
+
+/// How a fluid tile affects entities submerged in it.
+#[derive(Debug, Clone, Copy)]
+pub struct Fluid {
+    /// Upward push as a fraction of gravity. Above 1.0 makes entities float.
+    pub buoyancy: DimReal,
+    /// Fraction of velocity lost per second.
+    pub drag: DimReal,
+    /// Dealt to submerged entities whenever they aren't invulnerable.
+    pub contact_damage: DimReal,
 }
@@ -0,0 +1,312 @@
+use bitflags::bitflags;
+
+use crate::util::{Coord, CoordReal, Dim, DimReal};
+
+use super::{
+    anim::Animation, chunk::Tile, entity::EntityId, health::DamageSource, renderer::Pixel,
+};
+
+/// Position and physical properties. Required for the physics systems.
+#[derive(Clone)]
+pub struct Body {
+    pub position: CoordReal,
+    pub velocity: CoordReal,
+    pub mass: DimReal,
+    pub gravity_scale: DimReal,
+}
+
+impl Body {
+    pub fn new(position: CoordReal) -> Self {
+        Self {
+            position,
+            velocity: CoordReal::ZERO,
+            mass: 1.0,
+            gravity_scale: 1.0,
+        }
+    }
+
+    pub fn tile_pos(&self) -> Coord {
+        Coord {
+            row: self.position.row.round() as Dim,
+            col: self.position.col.round() as Dim,
+        }
+    }
+
+    /// The tile right below the entity's feet.
+    pub fn tile_below(&self) -> Coord {
+        let eps = 0.01;
+
+        Coord {
+            row: (self.position.row - 0.5 - eps).round() as Dim,
+            col: self.position.col.round() as Dim,
+        }
+    }
+}
+
+impl Default for Body {
+    fn default() -> Self {
+        Self::new(CoordReal::ZERO)
+    }
+}
+
+/// How an entity looks. Drawn at the position of its `Body`.
+#[derive(Clone)]
+pub struct Sprite {
+    animations: Vec<Animation>,
+    pub current_animation: usize,
+}
+
+impl Sprite {
+    pub fn new(animations: Vec<Animation>) -> Self {
+        Self {
+            animations,
+            current_animation: 0,
+        }
+    }
+
+    /// Shorthand for a sprite consisting of a single still frame.
+    pub fn still(pixel: Pixel) -> Self {
+        Self::new(vec![Animation::new(vec![pixel])])
+    }
+
+    pub fn next_frame(&mut self) -> &Pixel {
+        self.animations[self.current_animation].next_frame()
+    }
+}
+
+bitflags! {
+    /// Per-entity state bits. Contact flags are written by physics every tick;
+    /// the rest are set at spawn time or by gameplay code.
+    #[derive(Default)]
+    pub struct EntityFlags: u16 {
+        const PLAYER = 1 << 0;
+        /// Standing on an impassable tile.
+        const ON_GROUND = 1 << 1;
+        /// Bumped into an impassable tile from below.
+        const ON_CEILING = 1 << 2;
+        /// Pressed against an impassable tile horizontally.
+        const TOUCHING_WALL = 1 << 3;
+        /// Ignores incoming damage. Set while `Health::invulnerability_timer` runs.
+        const INVULNERABLE = 1 << 4;
+        /// Attacks the player.
+        const HOSTILE = 1 << 5;
+        /// Can be picked up by touching it.
+        const COLLECTIBLE = 1 << 6;
+        /// Passes through terrain.
+        const NO_CLIP = 1 << 7;
+        /// Kept around when its chunk unloads or it is far from the player.
+        const PERSISTENT = 1 << 8;
+        /// Holding onto a climbable tile; not affected by gravity.
+        const CLIMBING = 1 << 9;
+        /// Submerged in a fluid tile; swims instead of jumping.
+        const IN_FLUID = 1 << 10;
+        /// Ran out of health. Ignores controls.
+        const DEAD = 1 << 11;
+        /// Last walked to the left. Unset means facing right.
+        const FACING_LEFT = 1 << 12;
+
+        /// Everything set by collision solving.
+        const CONTACTS = Self::ON_GROUND.bits | Self::ON_CEILING.bits | Self::TOUCHING_WALL.bits;
+    }
+}
+
+#[derive(Clone)]
+pub enum MovementMode {
+    /// Completely static and not affected by gravity. No control.
+    Static,
+    /// Affected by gravity and physics interactions. Cannot be controlled.
+    Dynamic,
+    /// Walking and/or jumping. Customizable and controllable.
+    Walking(WalkingParams),
+}
+
+/// Tuning knobs for `MovementMode::Walking`. Speeds are in tiles per second,
+/// accelerations in tiles per second squared.
+#[derive(Clone, Copy)]
+pub struct WalkingParams {
+    /// Top horizontal speed reachable by walking alone.
+    pub max_speed: DimReal,
+    /// How fast the entity speeds up while a direction is held.
+    pub acceleration: DimReal,
+    /// How fast the entity slows down when no direction is held. Scaled by
+    /// the friction of the tile underfoot.
+    pub deceleration: DimReal,
+    /// Fraction of `acceleration` and `deceleration` available mid-air.
+    pub air_control: DimReal,
+    pub jump_impulse: DimReal,
+    /// Seconds after walking off a ledge during which a jump still works.
+    pub coyote_time: DimReal,
+    /// Seconds a jump press is remembered before landing.
+    pub jump_buffer_time: DimReal,
+    /// Multiplier for the upward velocity when jump is released mid-rise.
+    pub jump_cut: DimReal,
+    /// Vertical speed on ladders and vines.
+    pub climb_speed: DimReal,
+    /// Multiplier for `max_speed` while crouching.
+    pub crouch_speed_factor: DimReal,
+    /// Speed reached inside a fluid by holding jump to rise or down to dive.
+    pub swim_speed: DimReal,
+}
+
+impl Default for WalkingParams {
+    fn default() -> Self {
+        Self {
+            max_speed: 8.0,
+            acceleration: 60.0,
+            deceleration: 50.0,
+            air_control: 0.35,
+            jump_impulse: 10.0,
+            coyote_time: 0.1,
+            jump_buffer_time: 0.1,
+            jump_cut: 0.5,
+            climb_speed: 5.0,
+            crouch_speed_factor: 0.4,
+            swim_speed: 4.0,
+        }
+    }
+}
+
+/// Jump bookkeeping for walking entities. Attached together with a `MovementMode`.
+#[derive(Clone, Copy, Default)]
+pub struct JumpState {
+    /// Time left to jump after losing ground contact.
+    pub coyote_timer: DimReal,
+    /// Time left before a buffered jump press expires.
+    pub buffer_timer: DimReal,
+    /// Whether jump was held on the previous tick. Only fresh presses get buffered.
+    pub jump_held: bool,
+    /// Rising from a jump that can still be cut short.
+    pub rising: bool,
+}
+
+/// Written by whatever steers the entity (player input, AI), read by physics.
+#[derive(Debug, Clone, Copy)]
+pub struct MovementControls {
+    pub left: bool,
+    pub right: bool,
+    pub up: bool,
+    pub down: bool,
+    pub jump: bool,
+}
+
+impl MovementControls {
+    pub const EMPTY: Self = Self {
+        left: false,
+        right: false,
+        up: false,
+        down: false,
+        jump: false,
+    };
+
+    pub fn horizontal_direction(&self) -> DimReal {
+        if self.left && self.right {
+            0.0
+        } else if self.left {
+            -1.0
+        } else if self.right {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    pub fn vertical_direction(&self) -> DimReal {
+        if self.up && self.down {
+            0.0
+        } else if self.down {
+            -1.0
+        } else if self.up {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Hit points. Entities without this component can't be hurt.
+#[derive(Clone, Copy)]
+pub struct Health {
+    pub current: DimReal,
+    pub max: DimReal,
+    /// Seconds left during which further damage is ignored.
+    pub invulnerability_timer: DimReal,
+    /// Where the most recent damage came from.
+    pub last_damage: Option<DamageSource>,
+}
+
+impl Health {
+    pub fn new(max: DimReal) -> Self {
+        Self {
+            current: max,
+            max,
+            invulnerability_timer: 0.0,
+            last_damage: None,
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+}
+
+/// Behavior state of a mob. Drives its `MovementControls` like player input does.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AiState {
+    Idle,
+    Wander { direction: DimReal },
+    Chase,
+    Flee,
+}
+
+/// Mob brain. Hostile mobs are the ones flagged with `EntityFlags::HOSTILE`.
+#[derive(Debug, Clone, Copy)]
+pub struct Ai {
+    pub state: AiState,
+    /// Seconds until the current state times out.
+    pub state_timer: DimReal,
+    /// Distance at which hostile mobs notice the player.
+    pub sight_range: DimReal,
+    /// Damage dealt by touching the player. Only used by hostile mobs.
+    pub attack_damage: DimReal,
+}
+
+impl Ai {
+    pub fn new(sight_range: DimReal, attack_damage: DimReal) -> Self {
+        Self {
+            state: AiState::Idle,
+            state_timer: 0.0,
+            sight_range,
+            attack_damage,
+        }
+    }
+}
+
+/// What a projectile does to whatever it hits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProjectileEffect {
+    /// Remove the hit tile; vanish on entities.
+    BreakTile,
+    /// Hurt entities; get stuck in tiles until the lifetime runs out.
+    Stick(DimReal),
+    /// Explode on any hit with the given radius and power.
+    Explode { radius: DimReal, power: DimReal },
+}
+
+/// A thrown or shot entity. Moves through terrain on its own instead of
+/// being resolved by collision solving, so it should be `NO_CLIP`.
+#[derive(Debug, Clone, Copy)]
+pub struct Projectile {
+    /// Never hit by its own projectile.
+    pub owner: Option<EntityId>,
+    /// Seconds until the projectile disappears.
+    pub lifetime: DimReal,
+    pub effect: ProjectileEffect,
+    /// Stuck in a tile and no longer flying.
+    pub stuck: bool,
+}
+
+/// A tile knocked loose from the terrain. Placed back once it lands.
+#[derive(Clone, Copy)]
+pub struct FallingBlock {
+    pub tile: Tile,
+}
@@ -0,0 +1,315 @@
+use std::{collections::BTreeMap, fmt::Display, ops::RangeInclusive};
+
+use crossterm::event::{KeyCode, KeyEvent};
+
+use crate::util::{Coord, Dim};
+
+use super::{hud::Anchor, renderer::Screen, Game};
+
+/// Output lines kept in the console log.
+pub const CONSOLE_LOG_SIZE: usize = 8;
+
+pub const CONSOLE_HISTORY_SIZE: usize = 64;
+
+/// Success message or error, shown in the console log.
+pub type CommandResult = Result<String, String>;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Command {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub run: fn(&mut Game, &[&str]) -> CommandResult,
+    /// Suggestions for the argument at the given index.
+    pub complete: fn(usize) -> Vec<String>,
+}
+
+pub fn no_completions(_: usize) -> Vec<String> {
+    vec![]
+}
+
+#[derive(Default)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    log: Vec<String>,
+    history: Vec<String>,
+    /// Position in `history` while browsing it with the arrow keys.
+    history_cursor: Option<usize>,
+    commands: BTreeMap<&'static str, Command>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, command: Command) {
+        self.commands.insert(command.name, command);
+    }
+
+    pub fn log(&mut self, line: impl Into<String>) {
+        self.log.push(line.into());
+
+        if self.log.len() > CONSOLE_LOG_SIZE {
+            self.log.remove(0);
+        }
+    }
+
+    fn browse_history(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+
+        let cursor = match (self.history_cursor, older) {
+            (None, true) => Some(self.history.len() - 1),
+            (None, false) => None,
+            (Some(cursor), true) => Some(cursor.saturating_sub(1)),
+            (Some(cursor), false) if cursor + 1 < self.history.len() => Some(cursor + 1),
+            (Some(_), false) => None,
+        };
+
+        self.history_cursor = cursor;
+        self.input = match cursor {
+            Some(cursor) => self.history[cursor].clone(),
+            None => String::new(),
+        };
+    }
+
+    /// Complete the word being typed, or list the options if it's ambiguous.
+    fn complete(&mut self) {
+        let words: Vec<_> = self.input.split_whitespace().collect();
+        let typing_new_word = self.input.is_empty() || self.input.ends_with(' ');
+
+        let (prefix, arg_idx) = match typing_new_word {
+            true => ("", words.len()),
+            false => (words[words.len() - 1], words.len() - 1),
+        };
+
+        let options: Vec<String> = match arg_idx {
+            0 => self.commands.keys().map(|name| name.to_string()).collect(),
+            _ => match self.commands.get(words[0]) {
+                Some(command) => (command.complete)(arg_idx - 1),
+                None => vec![],
+            },
+        };
+
+        let matches: Vec<_> = options
+            .into_iter()
+            .filter(|option| option.starts_with(prefix))
+            .collect();
+
+        let Some(first) = matches.first() else {
+            return;
+        };
+
+        let common = matches.iter().fold(first.clone(), |common, option| {
+            common
+                .chars()
+                .zip(option.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect()
+        });
+
+        self.input.truncate(self.input.len() - prefix.len());
+        self.input.push_str(&common);
+
+        if matches.len() == 1 {
+            self.input.push(' ');
+        } else {
+            let listing = matches.join(" ");
+            self.log(listing);
+        }
+    }
+}
+
+impl Game {
+    pub fn toggle_console(&mut self) {
+        self.console.open = !self.console.open;
+        self.console.input.clear();
+        self.console.history_cursor = None;
+
+        // Don't keep walking while typing.
+        self.held_keys.clear();
+    }
+
+    /// Edit the command line. Only called while the console is open.
+    pub fn process_console_key(&mut self, key_event: KeyEvent) {
+        let console = &mut self.console;
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('`') => self.toggle_console(),
+            KeyCode::Enter => {
+                let line = std::mem::take(&mut console.input);
+                self.execute_command(&line);
+            }
+            KeyCode::Backspace => {
+                console.input.pop();
+            }
+            KeyCode::Tab => console.complete(),
+            KeyCode::Up => console.browse_history(true),
+            KeyCode::Down => console.browse_history(false),
+            KeyCode::Char(character) => console.input.push(character),
+            _ => (),
+        }
+    }
+
+    /// Parse and run a command line, logging the outcome.
+    pub fn execute_command(&mut self, line: &str) {
+        let line = line.trim();
+
+        if line.is_empty() {
+            return;
+        }
+
+        let console = &mut self.console;
+
+        if console.history.last().map(String::as_str) != Some(line) {
+            console.history.push(line.to_string());
+
+            if console.history.len() > CONSOLE_HISTORY_SIZE {
+                console.history.remove(0);
+            }
+        }
+
+        console.history_cursor = None;
+        console.log(format!("> {line}"));
+
+        let words: Vec<_> = line.split_whitespace().collect();
+
+        let Some(command) = console.commands.get(words[0]).copied() else {
+            console.log(format!("Unknown command `{}`, try `help`", words[0]));
+            return;
+        };
+
+        let output = match (command.run)(self, &words[1..]) {
+            Ok(output) => output,
+            Err(error) => format!("{error}. Usage: {}", command.usage),
+        };
+
+        if !output.is_empty() {
+            self.console.log(output);
+        }
+    }
+
+    pub fn display_console(&self, screen: &mut Screen) {
+        if !self.console.open {
+            return;
+        }
+
+        let width = screen.cols().max(0) as usize;
+
+        let lines: Vec<_> = self
+            .console
+            .log
+            .iter()
+            .cloned()
+            .chain([format!("> {}_", self.console.input)])
+            .map(|line| format!("{line:width$}"))
+            .collect();
+
+        screen.print_anchored(Anchor::BottomLeft, &lines);
+    }
+}
+
+/// Parse a command argument, naming it in the error.
+pub fn parse_arg<T: std::str::FromStr>(args: &[&str], idx: usize, name: &str) -> Result<T, String> {
+    let arg = args.get(idx).ok_or_else(|| format!("Missing <{name}>"))?;
+    arg.parse().map_err(|_| format!("Invalid <{name}> `{arg}`"))
+}
+
+/// Parse a numeric command argument that has to lie within `range`. Also
+/// rejects NaN, which is outside every range.
+pub fn parse_arg_in<T>(
+    args: &[&str],
+    idx: usize,
+    name: &str,
+    range: RangeInclusive<T>,
+) -> Result<T, String>
+where
+    T: std::str::FromStr + PartialOrd + Display,
+{
+    let value = parse_arg(args, idx, name)?;
+
+    if !range.contains(&value) {
+        return Err(format!(
+            "<{name}> must be between {} and {}",
+            range.start(),
+            range.end()
+        ));
+    }
+
+    Ok(value)
+}
+
+/// Commands that don't belong to any particular subsystem.
+pub fn register_commands(console: &mut Console) {
+    console.register(Command {
+        name: "help",
+        usage: "help [command]",
+        run: |game, args| match args.first() {
+            Some(name) => match game.console.commands.get(name) {
+                Some(command) => Ok(command.usage.to_string()),
+                None => Err(format!("Unknown command `{name}`")),
+            },
+            None => {
+                let names: Vec<_> = game.console.commands.keys().copied().collect();
+                Ok(names.join(" "))
+            }
+        },
+        complete: no_completions,
+    });
+
+    console.register(Command {
+        name: "tp",
+        usage: "tp <col> <row>",
+        run: |game, args| {
+            let col: Dim = parse_arg(args, 0, "col")?;
+            let row: Dim = parse_arg(args, 1, "row")?;
+
+            let body = game.get_player_body_mut().ok_or("No player")?;
+            let target = Coord { row, col };
+
+            body.position = target.to_real();
+            body.velocity = Default::default();
+
+            game.snap_camera_to_player();
+            game.load_chunks_around_camera();
+
+            Ok(format!("Teleported to {col} {row}"))
+        },
+        complete: no_completions,
+    });
+
+    console.register(Command {
+        name: "seed",
+        usage: "seed",
+        run: |game, _| Ok(format!("Seed: {}", game.terrain.seed())),
+        complete: no_completions,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_arg_in_accepts_values_in_range() {
+        assert_eq!(parse_arg_in(&["4"], 0, "count", 1..=64), Ok(4));
+        assert_eq!(parse_arg_in(&["-2.5"], 0, "value", -10.0..=10.0), Ok(-2.5));
+    }
+
+    #[test]
+    fn parse_arg_in_rejects_values_out_of_range() {
+        assert!(parse_arg_in(&["1000000"], 0, "count", 1..=64).is_err());
+        assert!(parse_arg_in(&["0"], 0, "count", 1..=64).is_err());
+        assert!(parse_arg_in(&["1e9"], 0, "radius", 1.0..=32.0).is_err());
+    }
+
+    #[test]
+    fn parse_arg_in_rejects_non_finite_values() {
+        for arg in ["NaN", "inf", "-inf"] {
+            assert!(parse_arg_in(&[arg], 0, "value", -100.0..=100.0).is_err());
+        }
+    }
+}
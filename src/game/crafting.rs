@@ -0,0 +1,304 @@
+use std::fmt;
+
+use crossterm::event::KeyCode;
+
+use crate::util::{Coord, Dim};
+
+use super::{
+    chunk::Tile,
+    inventory::{Item, ItemStack},
+    Game,
+};
+
+/// Built-in recipes, checked when the game starts.
+pub const RECIPES_SOURCE: &str = include_str!("../../data/recipes.txt");
+
+/// How many tiles away from the player a crafting station can be.
+pub const CRAFTING_STATION_RANGE: Dim = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipe {
+    pub inputs: Vec<ItemStack>,
+    pub outputs: Vec<ItemStack>,
+    /// Tile that has to be near the player to craft this.
+    pub station: Option<Tile>,
+}
+
+impl fmt::Display for Recipe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |stacks: &[ItemStack]| {
+            stacks
+                .iter()
+                .map(|stack| format!("{} {}", stack.count, stack.item.name()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        write!(f, "{} -> {}", list(&self.inputs), list(&self.outputs))?;
+
+        if let Some(station) = self.station {
+            write!(f, " @ {}", station.name())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecipeError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for RecipeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for RecipeError {}
+
+/// Parse a recipe file, making sure every item and station it mentions exists.
+pub fn parse_recipes(source: &str) -> Result<Vec<Recipe>, RecipeError> {
+    source
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.split('#').next().unwrap_or("").trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(line_number, line)| {
+            parse_recipe(line).map_err(|message| RecipeError {
+                line: line_number,
+                message,
+            })
+        })
+        .collect()
+}
+
+fn parse_recipe(line: &str) -> Result<Recipe, String> {
+    let (recipe, station) = match line.split_once('@') {
+        Some((recipe, station)) => (recipe, Some(station.trim())),
+        None => (line, None),
+    };
+
+    let (inputs, outputs) = recipe
+        .split_once("->")
+        .ok_or_else(|| String::from("missing `->` between inputs and outputs"))?;
+
+    let station = station
+        .map(|id| Tile::from_id(id).ok_or_else(|| format!("unknown station tile `{id}`")))
+        .transpose()?;
+
+    Ok(Recipe {
+        inputs: parse_stacks(inputs)?,
+        outputs: parse_stacks(outputs)?,
+        station,
+    })
+}
+
+fn parse_stacks(list: &str) -> Result<Vec<ItemStack>, String> {
+    list.split(',')
+        .map(|entry| {
+            let entry = entry.trim();
+
+            let (count, id) = entry
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| format!("expected `<count> <item>`, got `{entry}`"))?;
+
+            let count = match count.parse() {
+                Ok(count) if count > 0 => count,
+                _ => return Err(format!("invalid count `{count}`")),
+            };
+
+            let id = id.trim();
+            let item = Item::from_id(id).ok_or_else(|| format!("unknown item `{id}`"))?;
+
+            Ok(ItemStack::new(item, count))
+        })
+        .collect()
+}
+
+/// State of the open crafting menu.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CraftingMenu {
+    /// Index into the currently craftable recipes.
+    pub selected: usize,
+}
+
+impl Game {
+    /// Indices of the recipes the player has the items and station for.
+    pub fn craftable_recipes(&self) -> Vec<usize> {
+        let Some(inventory) = self.player_inventory() else {
+            return vec![];
+        };
+
+        self.recipes
+            .iter()
+            .enumerate()
+            .filter(|(_, recipe)| {
+                recipe
+                    .inputs
+                    .iter()
+                    .all(|stack| inventory.count(stack.item) >= stack.count)
+            })
+            .filter(|(_, recipe)| match recipe.station {
+                Some(station) => self.station_nearby(station),
+                None => true,
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    fn station_nearby(&self, station: Tile) -> bool {
+        let Some(center) = self.get_player_body().map(|body| body.tile_pos()) else {
+            return false;
+        };
+
+        let range = -CRAFTING_STATION_RANGE..=CRAFTING_STATION_RANGE;
+
+        range.clone().any(|row| {
+            range
+                .clone()
+                .any(|col| self.terrain[center + Coord { row, col }] == Some(station))
+        })
+    }
+
+    /// Swap the recipe's inputs for its outputs. Outputs that don't fit are dropped.
+    pub fn craft(&mut self, recipe_idx: usize) -> bool {
+        if !self.craftable_recipes().contains(&recipe_idx) {
+            return false;
+        }
+
+        let Some(player) = self.find_player() else {
+            return false;
+        };
+
+        let Some(position) = self.get_player_body().map(|body| body.tile_pos()) else {
+            return false;
+        };
+
+        let recipe = &self.recipes[recipe_idx];
+
+        let Some(inventory) = self.entities.inventories.get_mut(player) else {
+            return false;
+        };
+
+        for stack in &recipe.inputs {
+            inventory.remove(stack.item, stack.count);
+        }
+
+        let leftovers: Vec<_> = recipe
+            .outputs
+            .iter()
+            .filter_map(|&stack| inventory.insert(stack))
+            .collect();
+
+        for stack in leftovers {
+            self.drop_item(stack, position);
+        }
+
+        true
+    }
+
+    pub fn toggle_crafting_menu(&mut self) {
+        self.crafting_menu = match self.crafting_menu {
+            Some(_) => None,
+            None => Some(CraftingMenu::default()),
+        };
+    }
+
+    /// Navigate the open crafting menu.
+    pub fn process_crafting_input(&mut self) {
+        let close = self.is_key_pressed(KeyCode::Esc, false);
+        let up = self.is_key_pressed(KeyCode::Up, false);
+        let down = self.is_key_pressed(KeyCode::Down, false);
+        let confirm = self.is_key_pressed(KeyCode::Enter, false);
+
+        let craftable = self.craftable_recipes();
+
+        let Some(menu) = &mut self.crafting_menu else {
+            return;
+        };
+
+        if close {
+            self.crafting_menu = None;
+            return;
+        }
+
+        if craftable.is_empty() {
+            menu.selected = 0;
+            return;
+        }
+
+        if up {
+            menu.selected = menu.selected.checked_sub(1).unwrap_or(craftable.len() - 1);
+        }
+
+        if down {
+            menu.selected = (menu.selected + 1) % craftable.len();
+        }
+
+        menu.selected = menu.selected.min(craftable.len() - 1);
+
+        if confirm {
+            let recipe = craftable[menu.selected];
+            self.craft(recipe);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(source: &str) -> RecipeError {
+        parse_recipes(source).expect_err("recipes should be rejected")
+    }
+
+    #[test]
+    fn built_in_recipes_parse() {
+        assert!(parse_recipes(RECIPES_SOURCE).is_ok());
+    }
+
+    #[test]
+    fn parses_inputs_outputs_and_station() {
+        let recipes = parse_recipes("# comment\n2 stone -> 3 ladder @ workbench").unwrap();
+
+        assert_eq!(
+            recipes,
+            vec![Recipe {
+                inputs: vec![ItemStack::new(Item::Tile(Tile::Stone), 2)],
+                outputs: vec![ItemStack::new(Item::Tile(Tile::Ladder), 3)],
+                station: Some(Tile::Workbench),
+            }]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_items() {
+        let error = parse_error("4 dirt -> 1 workbench\n1 dirt -> 1 diamond");
+
+        assert_eq!(error.line, 2);
+        assert_eq!(error.message, "unknown item `diamond`");
+    }
+
+    #[test]
+    fn rejects_unknown_stations() {
+        let error = parse_error("1 dirt -> 1 stone @ furnace");
+
+        assert_eq!(error.line, 1);
+        assert_eq!(error.message, "unknown station tile `furnace`");
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        let error = parse_error("\n4 dirt 1 workbench");
+        assert_eq!(error.line, 2);
+        assert_eq!(error.message, "missing `->` between inputs and outputs");
+
+        let error = parse_error("dirt -> 1 workbench");
+        assert_eq!(error.message, "expected `<count> <item>`, got `dirt`");
+
+        let error = parse_error("0 dirt -> 1 workbench");
+        assert_eq!(error.message, "invalid count `0`");
+    }
+}
@@ -0,0 +1,175 @@
+use std::time::{Duration, Instant};
+
+use super::{components::EntityFlags, hud::Anchor, renderer::Screen, Game};
+
+/// Weight of the newest frame in the smoothed FPS counter.
+pub const FPS_SMOOTHING: f32 = 0.1;
+
+/// Parts of `Game::tick` timed separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickPhase {
+    Input,
+    Physics,
+    Collisions,
+    Update,
+    ChunkLoading,
+    Display,
+}
+
+impl TickPhase {
+    pub const ALL: [Self; 6] = [
+        Self::Input,
+        Self::Physics,
+        Self::Collisions,
+        Self::Update,
+        Self::ChunkLoading,
+        Self::Display,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Input => "input",
+            Self::Physics => "physics",
+            Self::Collisions => "collisions",
+            Self::Update => "update",
+            Self::ChunkLoading => "chunks",
+            Self::Display => "display",
+        }
+    }
+}
+
+/// Time spent in each phase of a single tick.
+#[derive(Debug, Clone, Default)]
+pub struct TickTimings {
+    phases: [Duration; TickPhase::ALL.len()],
+    lap_start: Option<Instant>,
+}
+
+impl TickTimings {
+    pub fn start() -> Self {
+        Self {
+            lap_start: Some(Instant::now()),
+            ..Default::default()
+        }
+    }
+
+    /// Add the time since the previous lap to `phase`.
+    pub fn lap(&mut self, phase: TickPhase) {
+        let now = Instant::now();
+
+        if let Some(start) = self.lap_start {
+            self.phases[phase as usize] += now - start;
+        }
+
+        self.lap_start = Some(now);
+    }
+
+    pub fn get(&self, phase: TickPhase) -> Duration {
+        self.phases[phase as usize]
+    }
+
+    pub fn total(&self) -> Duration {
+        self.phases.iter().sum()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DebugStats {
+    pub overlay_enabled: bool,
+    /// Timings of the last complete tick.
+    pub timings: TickTimings,
+    last_frame: Option<Instant>,
+    fps: f32,
+}
+
+impl DebugStats {
+    /// Call once per frame to update the FPS counter.
+    pub fn record_frame(&mut self) {
+        let now = Instant::now();
+
+        if let Some(last_frame) = self.last_frame {
+            let frame_time = (now - last_frame).as_secs_f32();
+
+            if frame_time > 0.0 {
+                let fps = 1.0 / frame_time;
+
+                self.fps = if self.fps == 0.0 {
+                    fps
+                } else {
+                    self.fps + (fps - self.fps) * FPS_SMOOTHING
+                };
+            }
+        }
+
+        self.last_frame = Some(now);
+    }
+
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+impl Game {
+    pub fn toggle_debug_overlay(&mut self) {
+        self.debug.overlay_enabled = !self.debug.overlay_enabled;
+    }
+
+    pub fn display_debug_overlay(&self, screen: &mut Screen) {
+        if !self.debug.overlay_enabled {
+            return;
+        }
+
+        let timings = &self.debug.timings;
+
+        let mut lines = vec![
+            format!("FPS: {:.1}", self.debug.fps()),
+            format!("Tick: {:.2} ms", millis(timings.total())),
+        ];
+
+        for phase in TickPhase::ALL {
+            lines.push(format!(
+                "  {}: {:.2} ms",
+                phase.name(),
+                millis(timings.get(phase))
+            ));
+        }
+
+        lines.push(format!(
+            "Chunks: {} loaded, {} active fluids",
+            self.terrain.loaded_chunks().len(),
+            self.terrain.active_fluids.len(),
+        ));
+        lines.push(format!("Entities: {}", self.entities.len()));
+
+        let player = self.find_player();
+        let body = player.and_then(|player| self.entities.bodies.get(player));
+
+        if let (Some(player), Some(body)) = (player, body) {
+            let on_ground = self.entities.has_flags(player, EntityFlags::ON_GROUND);
+
+            let tile_below = match self.terrain[body.tile_below()] {
+                Some(tile) => tile.name(),
+                None => "Air",
+            };
+
+            lines.push(format!(
+                "Pos: {:.2}, {:.2}",
+                body.position.col, body.position.row
+            ));
+            lines.push(format!(
+                "Vel: {:.2}, {:.2}",
+                body.velocity.col, body.velocity.row
+            ));
+            lines.push(format!("On ground: {on_ground}"));
+            lines.push(format!("Below: {tile_below}"));
+        }
+
+        lines.push(String::from("Throw: F arrow, G mining charge, B bomb"));
+
+        screen.print_anchored(Anchor::Left, &lines);
+    }
+}
@@ -1,21 +1,53 @@
 use crossterm::style::Color;
 
-use crate::util::{Coord, Dim};
+use crate::util::{Coord, Dim, DimReal};
 
 use super::{
-    chunk::{Tile, CHUNK_HEIGHT, CHUNK_WIDTH},
+    chunk::{Chunk, Tile, CHUNK_HEIGHT, CHUNK_WIDTH},
     renderer::{Pixel, Screen},
     Game,
 };
 
+fn entity_pixel_overwrite(output: &mut Pixel, replacement: &Pixel) {
+    let empty = Pixel::EMPTY;
+
+    if output.character == empty.character {
+        // Keep the background of fluids and other solid-colored tiles.
+        *output = Pixel {
+            bg_color: replacement.bg_color.or(output.bg_color),
+            ..*replacement
+        };
+    } else if output.bg_color.is_none() {
+        output.bg_color = Some(replacement.fg_color);
+    }
+}
+
 impl Game {
+    /// Draws every entity with a `Sprite` and a `Body`.
     pub fn display_entities(&mut self, screen: &mut Screen) {
-        for entity in &mut self.entities {
-            entity.display(&self.camera, screen);
+        let entities = &mut self.entities;
+
+        for (id, sprite) in entities.sprites.iter_mut() {
+            let Some(body) = entities.bodies.get(id) else {
+                continue;
+            };
+
+            let frame = sprite.next_frame();
+            frame.blit_custom(
+                body.tile_pos(),
+                &self.camera,
+                screen,
+                entity_pixel_overwrite,
+            );
         }
     }
 
     pub fn display_terrain(&mut self, screen: &mut Screen) {
+        if self.camera.zoom > 1 {
+            self.display_terrain_overview(screen);
+            return;
+        }
+
         for chunk in self.terrain.loaded_chunks() {
             for row in 0..CHUNK_HEIGHT {
                 for col in 0..CHUNK_WIDTH {
@@ -33,6 +65,116 @@ impl Game {
             }
         }
     }
+
+    /// Draws each screen cell as the most common tile in the block of world
+    /// tiles it covers, or as empty if the block is mostly empty.
+    fn display_terrain_overview(&self, screen: &mut Screen) {
+        let zoom = self.camera.zoom;
+
+        for screen_col in 0..screen.cols() {
+            let first_col = self
+                .camera
+                .unproject(
+                    Coord {
+                        row: 0,
+                        col: screen_col,
+                    },
+                    screen,
+                )
+                .col;
+
+            let columns: Vec<(&Chunk, Dim)> = (first_col..first_col + zoom)
+                .filter_map(|col| {
+                    let pos = Coord { row: 0, col };
+                    let chunk = self
+                        .terrain
+                        .loaded_chunks()
+                        .iter()
+                        .find(|chunk| chunk.contains(pos));
+
+                    chunk.map(|chunk| (chunk, col))
+                })
+                .collect();
+
+            if columns.is_empty() {
+                continue;
+            }
+
+            for screen_row in 0..screen.rows() {
+                let screen_point = Coord {
+                    row: screen_row,
+                    col: screen_col,
+                };
+
+                let first_row = self.camera.unproject(screen_point, screen).row;
+
+                let tiles = columns.iter().flat_map(|&(chunk, col)| {
+                    (first_row..first_row + zoom).map(move |row| chunk[Coord { row, col }])
+                });
+
+                if let Some(tile) = representative_tile(tiles, zoom * zoom) {
+                    screen[screen_point] = tile.display();
+                }
+            }
+        }
+    }
+}
+
+/// Most common tile among `tiles`, if at least half of the `area` is filled.
+fn representative_tile(tiles: impl Iterator<Item = Option<Tile>>, area: Dim) -> Option<Tile> {
+    let mut counts: Vec<(Tile, Dim)> = vec![];
+
+    for tile in tiles.flatten() {
+        match counts.iter_mut().find(|(counted, _)| *counted == tile) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((tile, 1)),
+        }
+    }
+
+    let filled: Dim = counts.iter().map(|(_, count)| count).sum();
+
+    if filled * 2 < area {
+        return None;
+    }
+
+    counts
+        .into_iter()
+        .max_by_key(|&(_, count)| count)
+        .map(|(tile, _)| tile)
+}
+
+impl Game {
+    /// Highlights the tile under the cursor and shows how far mining it got.
+    pub fn display_mining_cursor(&self, screen: &mut Screen) {
+        if self.camera.zoom > 1 {
+            return;
+        }
+
+        let Some(target) = self.cursor_world_position(screen) else {
+            return;
+        };
+
+        if !self.camera.is_visible(target, screen) {
+            return;
+        }
+
+        let stages = ['+', '.', ':', '%', '#'];
+
+        let stage = match (self.mining, self.terrain[target]) {
+            (Some(mining), Some(tile)) if mining.target == target => {
+                let fraction = mining.fraction(&tile);
+                1 + (fraction * (stages.len() - 2) as DimReal).round() as usize
+            }
+            _ => 0,
+        };
+
+        let screen_point = self.camera.project(target, screen);
+
+        let pixel = &mut screen[screen_point];
+
+        pixel.character = stages[stage];
+        pixel.fg_color = Color::White;
+    }
 }
 
 impl Tile {
@@ -57,6 +199,25 @@ impl Tile {
             GrassyDirt => solid(Color::DarkGreen),
             Grass => object('\"', Color::DarkGreen),
             Flower(color) => object('*', *color),
+            Ladder => object('H', Color::DarkYellow),
+            Vine => object('|', Color::Green),
+            Platform => object('=', Color::DarkYellow),
+            Water => solid(Color::Blue),
+            Lava => solid(Color::Red),
+            Sand => solid(Color::DarkYellow),
+            Gravel => solid(Color::Grey),
+            Checkpoint => object('!', Color::Magenta),
+            Workbench => object('n', Color::DarkYellow),
+            IronOre => Pixel {
+                character: '%',
+                fg_color: Color::Grey,
+                bg_color: Some(Color::DarkGrey),
+            },
+            Tnt => Pixel {
+                character: 'T',
+                fg_color: Color::White,
+                bg_color: Some(Color::DarkRed),
+            },
         }
     }
 }
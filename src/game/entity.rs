@@ -1,115 +1,310 @@
-use std::collections::HashSet;
-
-use crate::util::{Coord, CoordReal, Dim, DimReal};
+use std::collections::{BTreeMap, BTreeSet};
 
 use super::{
-    anim::Animation,
-    renderer::{Camera, Pixel, Screen},
+    components::{
+        Ai, Body, EntityFlags, FallingBlock, Health, JumpState, MovementControls, MovementMode,
+        Projectile, Sprite,
+    },
+    inventory::{Inventory, ItemDrop},
+    pathfinding::Navigation,
 };
 
-fn entity_pixel_overwrite(output: &mut Pixel, replacement: &Pixel) {
-    let empty = Pixel::EMPTY;
+/// Opaque handle to an entity. IDs are never reused within a game session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EntityId(u64);
+
+/// Holds every component of a single type, keyed by the owning entity.
+///
+/// Iteration order is the spawn order of the entities.
+#[derive(Clone)]
+pub struct Storage<T> {
+    components: BTreeMap<EntityId, T>,
+}
+
+impl<T> Storage<T> {
+    pub fn new() -> Self {
+        Self {
+            components: BTreeMap::new(),
+        }
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&T> {
+        self.components.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: EntityId) -> Option<&mut T> {
+        self.components.get_mut(&id)
+    }
+
+    pub fn insert(&mut self, id: EntityId, component: T) {
+        self.components.insert(id, component);
+    }
+
+    pub fn remove(&mut self, id: EntityId) -> Option<T> {
+        self.components.remove(&id)
+    }
+
+    pub fn contains(&self, id: EntityId) -> bool {
+        self.components.contains_key(&id)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = EntityId> + '_ {
+        self.components.keys().copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (EntityId, &T)> {
+        self.components
+            .iter()
+            .map(|(id, component)| (*id, component))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (EntityId, &mut T)> {
+        self.components
+            .iter_mut()
+            .map(|(id, component)| (*id, component))
+    }
+}
 
-    if output.character == empty.character {
-        *output = *replacement;
-    } else if output.bg_color.is_none() {
-        output.bg_color = Some(replacement.fg_color);
+impl<T> Default for Storage<T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-#[derive(Clone)]
-pub struct Entity {
-    pub position: CoordReal,
-    pub velocity: CoordReal,
-    pub mass: DimReal,
-    pub gravity_scale: DimReal,
-    animations: Vec<Animation>,
-    pub current_animation: usize,
-    // TODO: use bitflags instead.
-    pub flags: HashSet<EntityFlag>,
-    pub movement_mode: MovementMode,
-    pub movement_controls: MovementControls,
-    pub deletion_flag: bool,
-    // TODO: put into `EntityFlag`, perhaps?
-    pub on_ground: bool,
+/// The set of all living entities and their components.
+///
+/// Each component type lives in its own storage, so systems can borrow
+/// exactly the storages they need (and several of them mutably at once).
+pub struct Entities {
+    next_id: u64,
+    alive: BTreeSet<EntityId>,
+    marked_for_deletion: BTreeSet<EntityId>,
+    pub bodies: Storage<Body>,
+    pub sprites: Storage<Sprite>,
+    pub movement_modes: Storage<MovementMode>,
+    pub movement_controls: Storage<MovementControls>,
+    pub jump_states: Storage<JumpState>,
+    pub falling_blocks: Storage<FallingBlock>,
+    pub healths: Storage<Health>,
+    pub ais: Storage<Ai>,
+    pub navigations: Storage<Navigation>,
+    pub projectiles: Storage<Projectile>,
+    pub inventories: Storage<Inventory>,
+    pub item_drops: Storage<ItemDrop>,
+    pub flags: Storage<EntityFlags>,
 }
 
-impl Entity {
-    pub fn new(animations: Vec<Animation>) -> Self {
+impl Entities {
+    pub fn new() -> Self {
         Self {
-            position: CoordReal::ZERO,
-            velocity: CoordReal::ZERO,
-            mass: 1.0,
-            gravity_scale: 1.0,
-            animations,
-            current_animation: 0,
-            flags: HashSet::new(),
-            movement_mode: MovementMode::Static,
-            movement_controls: MovementControls::EMPTY,
-            deletion_flag: false,
-            on_ground: false,
+            next_id: 0,
+            alive: BTreeSet::new(),
+            marked_for_deletion: BTreeSet::new(),
+            bodies: Storage::new(),
+            sprites: Storage::new(),
+            movement_modes: Storage::new(),
+            movement_controls: Storage::new(),
+            jump_states: Storage::new(),
+            falling_blocks: Storage::new(),
+            healths: Storage::new(),
+            ais: Storage::new(),
+            navigations: Storage::new(),
+            projectiles: Storage::new(),
+            inventories: Storage::new(),
+            item_drops: Storage::new(),
+            flags: Storage::new(),
+        }
+    }
+
+    pub fn spawn(&mut self, builder: EntityBuilder) -> EntityId {
+        let id = EntityId(self.next_id);
+        self.next_id += 1;
+
+        self.alive.insert(id);
+
+        if let Some(body) = builder.body {
+            self.bodies.insert(id, body);
+        }
+
+        if let Some(sprite) = builder.sprite {
+            self.sprites.insert(id, sprite);
+        }
+
+        if let Some(mode) = builder.movement_mode {
+            self.movement_modes.insert(id, mode);
+            self.movement_controls.insert(id, MovementControls::EMPTY);
+            self.jump_states.insert(id, JumpState::default());
+        }
+
+        if let Some(falling_block) = builder.falling_block {
+            self.falling_blocks.insert(id, falling_block);
+        }
+
+        if let Some(health) = builder.health {
+            self.healths.insert(id, health);
+        }
+
+        if let Some(ai) = builder.ai {
+            self.ais.insert(id, ai);
+        }
+
+        if let Some(navigation) = builder.navigation {
+            self.navigations.insert(id, navigation);
+        }
+
+        if let Some(projectile) = builder.projectile {
+            self.projectiles.insert(id, projectile);
         }
+
+        if let Some(inventory) = builder.inventory {
+            self.inventories.insert(id, inventory);
+        }
+
+        if let Some(item_drop) = builder.item_drop {
+            self.item_drops.insert(id, item_drop);
+        }
+
+        self.flags.insert(id, builder.flags);
+
+        id
     }
 
-    pub fn tile_pos(&self) -> Coord {
-        Coord {
-            row: self.position.row.round() as Dim,
-            col: self.position.col.round() as Dim,
+    /// Remove the entity and all of its components immediately.
+    pub fn despawn(&mut self, id: EntityId) {
+        self.alive.remove(&id);
+        self.marked_for_deletion.remove(&id);
+
+        self.bodies.remove(id);
+        self.sprites.remove(id);
+        self.movement_modes.remove(id);
+        self.movement_controls.remove(id);
+        self.jump_states.remove(id);
+        self.falling_blocks.remove(id);
+        self.healths.remove(id);
+        self.ais.remove(id);
+        self.navigations.remove(id);
+        self.projectiles.remove(id);
+        self.inventories.remove(id);
+        self.item_drops.remove(id);
+        self.flags.remove(id);
+    }
+
+    /// Schedule the entity for removal at the end of the current tick.
+    pub fn mark_for_deletion(&mut self, id: EntityId) {
+        if self.alive.contains(&id) {
+            self.marked_for_deletion.insert(id);
         }
     }
 
-    pub fn display(&mut self, camera: &Camera, screen: &mut Screen) {
-        let tile_pos = self.tile_pos();
-        let frame = self.animations[self.current_animation].next_frame();
-        frame.blit_custom(tile_pos, camera, screen, entity_pixel_overwrite);
+    pub fn delete_marked(&mut self) {
+        let marked = std::mem::take(&mut self.marked_for_deletion);
+
+        for id in marked {
+            self.despawn(id);
+        }
     }
-}
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub enum EntityFlag {
-    Player,
+    pub fn ids(&self) -> impl Iterator<Item = EntityId> + '_ {
+        self.alive.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.alive.len()
+    }
+
+    pub fn has_flags(&self, id: EntityId, flags: EntityFlags) -> bool {
+        match self.flags.get(id) {
+            Some(entity_flags) => entity_flags.contains(flags),
+            None => false,
+        }
+    }
 }
 
-#[derive(Clone)]
-pub enum MovementMode {
-    /// Completely static and not affected by gravity. No control.
-    Static,
-    /// Affected by gravity and physics interactions. Cannot be controlled.
-    Dynamic,
-    /// Walking and/or jumping. Customizable and controllable.
-    Walking {
-        walking_speed: DimReal,
-        jump_impulse: DimReal,
-    },
+impl Default for Entities {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct MovementControls {
-    pub left: bool,
-    pub right: bool,
-    pub up: bool,
-    pub down: bool,
-    pub jump: bool,
+/// A bundle of components to be turned into an entity with `Game::spawn`.
+#[derive(Clone, Default)]
+pub struct EntityBuilder {
+    body: Option<Body>,
+    sprite: Option<Sprite>,
+    movement_mode: Option<MovementMode>,
+    falling_block: Option<FallingBlock>,
+    health: Option<Health>,
+    ai: Option<Ai>,
+    navigation: Option<Navigation>,
+    projectile: Option<Projectile>,
+    inventory: Option<Inventory>,
+    item_drop: Option<ItemDrop>,
+    flags: EntityFlags,
 }
 
-impl MovementControls {
-    pub const EMPTY: Self = Self {
-        left: false,
-        right: false,
-        up: false,
-        down: false,
-        jump: false,
-    };
-
-    pub fn horizontal_direction(&self) -> DimReal {
-        if self.left && self.right {
-            0.0
-        } else if self.left {
-            -1.0
-        } else if self.right {
-            1.0
-        } else {
-            0.0
-        }
+impl EntityBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_body(mut self, body: Body) -> Self {
+        self.body = Some(body);
+        self
+    }
+
+    pub fn with_sprite(mut self, sprite: Sprite) -> Self {
+        self.sprite = Some(sprite);
+        self
+    }
+
+    /// Also attaches empty `MovementControls` and a `JumpState`, so the entity
+    /// can be steered.
+    pub fn with_movement_mode(mut self, mode: MovementMode) -> Self {
+        self.movement_mode = Some(mode);
+        self
+    }
+
+    pub fn with_falling_block(mut self, falling_block: FallingBlock) -> Self {
+        self.falling_block = Some(falling_block);
+        self
+    }
+
+    pub fn with_health(mut self, health: Health) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    pub fn with_ai(mut self, ai: Ai) -> Self {
+        self.ai = Some(ai);
+        self
+    }
+
+    pub fn with_navigation(mut self, navigation: Navigation) -> Self {
+        self.navigation = Some(navigation);
+        self
+    }
+
+    pub fn with_projectile(mut self, projectile: Projectile) -> Self {
+        self.projectile = Some(projectile);
+        self
+    }
+
+    pub fn with_inventory(mut self, inventory: Inventory) -> Self {
+        self.inventory = Some(inventory);
+        self
+    }
+
+    pub fn with_item_drop(mut self, item_drop: ItemDrop) -> Self {
+        self.item_drop = Some(item_drop);
+        self
+    }
+
+    pub fn with_flags(mut self, flags: EntityFlags) -> Self {
+        self.flags |= flags;
+        self
+    }
+
+    pub fn has_flags(&self, flags: EntityFlags) -> bool {
+        self.flags.contains(flags)
     }
 }
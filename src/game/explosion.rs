@@ -0,0 +1,204 @@
+use crate::util::{Coord, CoordReal, Dim, DimReal};
+
+use super::{
+    chunk::Tile,
+    components::MovementMode,
+    console::{no_completions, parse_arg, parse_arg_in, Command, Console},
+    health::DamageSource,
+    inventory::ItemStack,
+    Game,
+};
+
+/// Impulse applied per unit of explosion power at the center.
+pub const EXPLOSION_IMPULSE: DimReal = 150.0;
+
+/// Damage dealt per unit of explosion power at the center.
+pub const EXPLOSION_DAMAGE: DimReal = 10.0;
+
+/// Camera trauma per unit of explosion power, when the camera is at the center.
+pub const EXPLOSION_SHAKE: DimReal = 0.15;
+/// Distance, in explosion radii, at which explosions stop shaking the camera.
+pub const EXPLOSION_SHAKE_RANGE: DimReal = 8.0;
+
+/// Largest radius the `explode` command accepts.
+pub const MAX_COMMAND_RADIUS: DimReal = 32.0;
+/// Largest power the `explode` command accepts.
+pub const MAX_COMMAND_POWER: DimReal = 100.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Explosion {
+    pub center: CoordReal,
+    pub radius: DimReal,
+    /// Compared against tile durability, falling off linearly to 0 at `radius`.
+    pub power: DimReal,
+}
+
+impl Explosion {
+    pub fn new(center: CoordReal, radius: DimReal, power: DimReal) -> Self {
+        Self {
+            center,
+            radius,
+            power,
+        }
+    }
+
+    /// Explosion power left at the given point.
+    fn strength_at(&self, point: CoordReal) -> DimReal {
+        let distance = (point - self.center).length();
+        self.power * (1.0 - distance / self.radius).max(0.0)
+    }
+}
+
+impl Tile {
+    /// The explosion a tile sets off when it is destroyed, if any.
+    pub fn explosion(&self, pos: Coord) -> Option<Explosion> {
+        match self {
+            Tile::Tnt => Some(Explosion::new(pos.to_real(), 4.0, 4.0)),
+            _ => None,
+        }
+    }
+}
+
+impl Game {
+    /// Remove a tile from the world. Explosive tiles go off on the next
+    /// `detonate_explosions` and the rest drop as items. Returns the removed tile.
+    pub fn destroy_tile(&mut self, pos: Coord) -> Option<Tile> {
+        let tile = self.terrain[pos]?;
+        self.terrain.set_tile(pos, None);
+        self.drop_tile(tile, pos);
+
+        Some(tile)
+    }
+
+    /// Queue the explosion of, or drop the item for, a tile that left the
+    /// terrain at `pos`.
+    pub fn drop_tile(&mut self, tile: Tile, pos: Coord) {
+        if let Some(explosion) = tile.explosion(pos) {
+            self.pending_explosions.push(explosion);
+        } else if let Some(item) = tile.drop_item() {
+            self.drop_item(ItemStack::new(item, 1), pos);
+        }
+    }
+
+    /// Blow up the terrain and push entities away, along with any explosive
+    /// tiles caught in the blast. Returns the number of destroyed tiles.
+    pub fn explode(&mut self, explosion: Explosion) -> usize {
+        self.pending_explosions.push(explosion);
+        self.detonate_explosions()
+    }
+
+    /// Set off every pending explosion, including the ones they chain into.
+    /// Returns the number of destroyed tiles.
+    pub fn detonate_explosions(&mut self) -> usize {
+        let mut destroyed = 0;
+
+        while let Some(explosion) = self.pending_explosions.pop() {
+            destroyed += self.carve_terrain(&explosion);
+            self.apply_knockback(&explosion);
+            self.shake_camera_for(&explosion);
+        }
+
+        destroyed
+    }
+
+    fn carve_terrain(&mut self, explosion: &Explosion) -> usize {
+        let reach = explosion.radius.ceil() as Dim;
+
+        let center = Coord {
+            row: explosion.center.row.round() as Dim,
+            col: explosion.center.col.round() as Dim,
+        };
+
+        let mut destroyed = 0;
+
+        for row in -reach..=reach {
+            for col in -reach..=reach {
+                let pos = center + Coord { row, col };
+
+                let Some(tile) = self.terrain[pos] else {
+                    continue;
+                };
+
+                if explosion.strength_at(pos.to_real()) > tile.durability() {
+                    self.destroy_tile(pos);
+                    destroyed += 1;
+                }
+            }
+        }
+
+        destroyed
+    }
+
+    fn apply_knockback(&mut self, explosion: &Explosion) {
+        let mut damaged = vec![];
+        let entities = &mut self.entities;
+
+        for (id, body) in entities.bodies.iter_mut() {
+            let strength = explosion.strength_at(body.position);
+
+            if strength <= 0.0 {
+                continue;
+            }
+
+            if entities.healths.contains(id) {
+                damaged.push((id, strength * EXPLOSION_DAMAGE));
+            }
+
+            if matches!(
+                entities.movement_modes.get(id),
+                None | Some(MovementMode::Static)
+            ) {
+                continue;
+            }
+
+            let offset = body.position - explosion.center;
+            let distance = offset.length();
+
+            let direction = if distance > 0.0 {
+                offset * (1.0 / distance)
+            } else {
+                CoordReal { row: 1.0, col: 0.0 }
+            };
+
+            body.velocity += direction * (strength * EXPLOSION_IMPULSE / body.mass);
+        }
+
+        for (id, amount) in damaged {
+            self.deal_damage(id, amount, DamageSource::Explosion);
+        }
+    }
+
+    fn shake_camera_for(&mut self, explosion: &Explosion) {
+        let distance = (self.camera.position.to_real() - explosion.center).length();
+        let falloff = (1.0 - distance / (explosion.radius * EXPLOSION_SHAKE_RANGE)).max(0.0);
+
+        self.shake_camera(explosion.power * EXPLOSION_SHAKE * falloff);
+    }
+}
+
+pub fn register_commands(console: &mut Console) {
+    console.register(Command {
+        name: "explode",
+        usage: "explode <col> <row> [radius] [power]",
+        run: |game, args| {
+            let col: Dim = parse_arg(args, 0, "col")?;
+            let row: Dim = parse_arg(args, 1, "row")?;
+
+            let radius: DimReal = match args.get(2) {
+                Some(_) => parse_arg_in(args, 2, "radius", 1.0..=MAX_COMMAND_RADIUS)?,
+                None => 4.0,
+            };
+
+            let power: DimReal = match args.get(3) {
+                Some(_) => parse_arg_in(args, 3, "power", 0.0..=MAX_COMMAND_POWER)?,
+                None => radius,
+            };
+
+            let center = Coord { row, col }.to_real();
+            let destroyed = game.explode(Explosion::new(center, radius, power));
+
+            Ok(format!("Destroyed {destroyed} tiles"))
+        },
+        complete: no_completions,
+    });
+}
@@ -0,0 +1,126 @@
+use crate::util::{Coord, Dim, DimReal};
+
+use super::{
+    components::{Body, EntityFlags, FallingBlock, MovementMode, Sprite},
+    entity::EntityBuilder,
+    health::{DamageEvent, DamageSource},
+    Game,
+};
+
+/// How far up a landed block may look for a free tile to settle in.
+const SETTLE_SEARCH_HEIGHT: Dim = 4;
+
+/// Damage per tile per second of falling speed dealt to entities in the way.
+pub const FALLING_BLOCK_DAMAGE: DimReal = 2.0;
+
+pub fn new_falling_block(block: FallingBlock, pos: Coord) -> EntityBuilder {
+    let mut body = Body::new(pos.to_real());
+
+    body.mass = 20.0;
+    // Sink through water instead of floating on it.
+    body.gravity_scale = 2.0;
+
+    EntityBuilder::new()
+        .with_body(body)
+        .with_sprite(Sprite::still(block.tile.display()))
+        .with_movement_mode(MovementMode::Dynamic)
+        .with_falling_block(block)
+}
+
+impl Game {
+    /// Detach unsupported falling tiles from the terrain.
+    pub fn drop_unsupported_blocks(&mut self) {
+        let candidates = std::mem::take(&mut self.terrain.falling_candidates);
+
+        for pos in candidates {
+            if !self.terrain.contains(pos) {
+                continue;
+            }
+
+            let tile = match self.terrain[pos] {
+                Some(tile) if tile.falls() => tile,
+                _ => continue,
+            };
+
+            let supported = match self.terrain[pos + Coord::DOWN] {
+                Some(below) => below.is_impassable(),
+                // Don't let blocks leak out of the bottom of the world.
+                None => pos.row <= 0,
+            };
+
+            if supported {
+                continue;
+            }
+
+            // Also queues the tile above, so whole stacks collapse in turn.
+            self.terrain.set_tile(pos, None);
+            self.spawn(new_falling_block(FallingBlock { tile }, pos));
+        }
+    }
+
+    /// Turn landed falling blocks back into tiles, hurting whatever they fall on.
+    pub fn settle_falling_blocks(&mut self) {
+        let entities = &self.entities;
+
+        let mut landed = vec![];
+        let mut lost = vec![];
+
+        for (id, block) in entities.falling_blocks.iter() {
+            let Some(body) = entities.bodies.get(id) else {
+                continue;
+            };
+
+            let speed = -body.velocity.row;
+
+            if speed > 0.0 {
+                for (victim, _) in entities.healths.iter() {
+                    let hit = match entities.bodies.get(victim) {
+                        Some(victim_body) => victim_body.tile_pos() == body.tile_pos(),
+                        None => false,
+                    };
+
+                    if hit {
+                        self.damage_events.push(DamageEvent {
+                            target: victim,
+                            amount: speed * FALLING_BLOCK_DAMAGE,
+                            source: DamageSource::FallingBlock,
+                        });
+                    }
+                }
+            }
+
+            if entities.has_flags(id, EntityFlags::ON_GROUND) {
+                landed.push((id, *block, body.tile_pos()));
+            } else if !self.terrain.contains(body.tile_pos()) {
+                lost.push(id);
+            }
+        }
+
+        // Fell out of the loaded world.
+        for id in lost {
+            self.entities.mark_for_deletion(id);
+        }
+
+        for (id, block, pos) in landed {
+            let free_pos = (0..SETTLE_SEARCH_HEIGHT)
+                .map(|offset| {
+                    pos + Coord {
+                        row: offset,
+                        col: 0,
+                    }
+                })
+                .find(|&pos| match self.terrain[pos] {
+                    Some(tile) => tile.fluid().is_some(),
+                    None => self.terrain.contains(pos),
+                });
+
+            // Buried too deep to settle, so break it instead of losing it.
+            match free_pos {
+                Some(free_pos) => self.terrain.set_tile(free_pos, Some(block.tile)),
+                None => self.drop_tile(block.tile, pos),
+            }
+
+            self.entities.mark_for_deletion(id);
+        }
+    }
+}
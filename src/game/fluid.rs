@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+
+use crate::util::Coord;
+
+use super::{chunk::Tile, terrain::Terrain, Game};
+
+/// Fluids move once every this many ticks.
+pub const FLUID_UPDATE_INTERVAL: u64 = 4;
+
+/// Lava only moves on every this many fluid updates.
+pub const LAVA_SLOWDOWN: u64 = 3;
+
+impl Game {
+    pub fn update_fluids(&mut self) {
+        if !self.tick_count.is_multiple_of(FLUID_UPDATE_INTERVAL) {
+            return;
+        }
+
+        let step = self.tick_count / FLUID_UPDATE_INTERVAL;
+        self.terrain.update_fluids(step);
+    }
+}
+
+impl Terrain {
+    /// Run one step of the fluid cellular automaton.
+    ///
+    /// Only cells in `active_fluids` are processed. Cells that can't move
+    /// drop out of the set until a nearby tile changes with `set_tile`.
+    fn update_fluids(&mut self, step: u64) {
+        let active = std::mem::take(&mut self.active_fluids);
+        let mut moved = HashSet::new();
+
+        // Rows go up, so this runs bottom to top and lets whole columns fall at once.
+        for pos in active {
+            if moved.contains(&pos) || !self.contains(pos) {
+                continue;
+            }
+
+            let tile = match self[pos] {
+                Some(tile) if tile.fluid().is_some() => tile,
+                _ => continue,
+            };
+
+            if self.solidify_lava(pos, tile) {
+                continue;
+            }
+
+            if tile == Tile::Lava && !step.is_multiple_of(LAVA_SLOWDOWN) {
+                self.active_fluids.insert(pos);
+                continue;
+            }
+
+            if let Some(target) = self.flow_target(pos) {
+                self.set_tile(pos, None);
+                self.set_tile(target, Some(tile));
+                moved.insert(target);
+            }
+        }
+    }
+
+    fn is_free(&self, pos: Coord) -> bool {
+        self.contains(pos) && self[pos].is_none()
+    }
+
+    fn flow_target(&self, pos: Coord) -> Option<Coord> {
+        let below = pos + Coord::DOWN;
+
+        if self.is_free(below) {
+            return Some(below);
+        }
+
+        // Fluid with more fluid on top gets pushed sideways; a lone surface
+        // cell only spills over edges, so puddles eventually settle.
+        let pressured = matches!(self[pos + Coord::UP], Some(tile) if tile.fluid().is_some());
+
+        let mut sides = [Coord::LEFT, Coord::RIGHT];
+
+        if rand::random() {
+            sides.reverse();
+        }
+
+        sides
+            .into_iter()
+            .map(|side| pos + side)
+            .filter(|&target| self.is_free(target))
+            .find(|&target| pressured || self.is_free(target + Coord::DOWN))
+    }
+
+    /// Turn lava touching water into stone. Returns `true` if the tile at
+    /// `pos` itself solidified.
+    fn solidify_lava(&mut self, pos: Coord, tile: Tile) -> bool {
+        let other = match tile {
+            Tile::Water => Tile::Lava,
+            Tile::Lava => Tile::Water,
+            _ => return false,
+        };
+
+        for direction in [Coord::UP, Coord::DOWN, Coord::LEFT, Coord::RIGHT] {
+            let neighbor = pos + direction;
+
+            if self[neighbor] != Some(other) {
+                continue;
+            }
+
+            let lava_pos = if tile == Tile::Lava { pos } else { neighbor };
+            self.set_tile(lava_pos, Some(Tile::Stone));
+
+            return tile == Tile::Lava;
+        }
+
+        false
+    }
+}
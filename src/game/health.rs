@@ -0,0 +1,137 @@
+use crate::util::DimReal;
+
+use super::{
+    components::{EntityFlags, MovementControls},
+    entity::EntityId,
+    physics::TIMESTEP,
+    Game,
+};
+
+/// Seconds of invulnerability granted after taking damage.
+pub const INVULNERABILITY_TIME: DimReal = 0.75;
+
+/// Landing slower than this (in tiles per second) doesn't hurt.
+pub const SAFE_FALL_SPEED: DimReal = 18.0;
+
+/// Damage per tile per second of impact speed above `SAFE_FALL_SPEED`.
+pub const FALL_DAMAGE_PER_SPEED: DimReal = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageSource {
+    Fall,
+    Fluid,
+    FallingBlock,
+    Entity(EntityId),
+    Projectile { owner: Option<EntityId> },
+    Explosion,
+}
+
+impl DamageSource {
+    /// Shown on the death screen.
+    pub fn death_message(&self) -> &'static str {
+        match self {
+            Self::Fall => "Fell from a high place",
+            Self::Fluid => "Tried to swim in lava",
+            Self::FallingBlock => "Crushed by a falling block",
+            Self::Entity(_) => "Killed by a mob",
+            Self::Projectile { .. } => "Shot",
+            Self::Explosion => "Blown up",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DamageEvent {
+    pub target: EntityId,
+    pub amount: DimReal,
+    pub source: DamageSource,
+}
+
+pub fn fall_damage(impact_speed: DimReal) -> DimReal {
+    (impact_speed - SAFE_FALL_SPEED).max(0.0) * FALL_DAMAGE_PER_SPEED
+}
+
+impl Game {
+    /// Queue damage to be applied on the next `apply_damage`.
+    pub fn deal_damage(&mut self, target: EntityId, amount: DimReal, source: DamageSource) {
+        self.damage_events.push(DamageEvent {
+            target,
+            amount,
+            source,
+        });
+    }
+
+    /// Apply queued damage events, respecting invulnerability, and handle deaths.
+    pub fn apply_damage(&mut self) {
+        let entities = &mut self.entities;
+
+        for (id, health) in entities.healths.iter_mut() {
+            health.invulnerability_timer = (health.invulnerability_timer - TIMESTEP).max(0.0);
+
+            if let Some(flags) = entities.flags.get_mut(id) {
+                flags.set(
+                    EntityFlags::INVULNERABLE,
+                    health.invulnerability_timer > 0.0,
+                );
+            }
+        }
+
+        // A hit starts invulnerability, so only one event per target can land
+        // each tick. Keep the biggest instead of whichever was queued first.
+        let mut hits: Vec<DamageEvent> = vec![];
+
+        for event in std::mem::take(&mut self.damage_events) {
+            match hits.iter_mut().find(|hit| hit.target == event.target) {
+                Some(hit) if hit.amount >= event.amount => (),
+                Some(hit) => *hit = event,
+                None => hits.push(event),
+            }
+        }
+
+        for hit in hits {
+            if self
+                .entities
+                .has_flags(hit.target, EntityFlags::INVULNERABLE)
+            {
+                continue;
+            }
+
+            let Some(health) = self.entities.healths.get_mut(hit.target) else {
+                continue;
+            };
+
+            if health.is_dead() {
+                continue;
+            }
+
+            health.current = (health.current - hit.amount).max(0.0);
+            health.invulnerability_timer = INVULNERABILITY_TIME;
+            health.last_damage = Some(hit.source);
+
+            let dead = health.is_dead();
+
+            if let Some(flags) = self.entities.flags.get_mut(hit.target) {
+                flags.insert(EntityFlags::INVULNERABLE);
+            }
+
+            if dead {
+                self.kill(hit.target);
+            }
+        }
+    }
+
+    fn kill(&mut self, id: EntityId) {
+        if self.entities.has_flags(id, EntityFlags::PLAYER) {
+            // Keep the player around for the death state.
+            if let Some(flags) = self.entities.flags.get_mut(id) {
+                flags.insert(EntityFlags::DEAD);
+            }
+
+            if let Some(controls) = self.entities.movement_controls.get_mut(id) {
+                *controls = MovementControls::EMPTY;
+            }
+        } else {
+            self.entities.mark_for_deletion(id);
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use crate::util::{Coord, Dim};
+
+use super::{
+    camera::CameraMode,
+    components::EntityFlags,
+    inventory::{Item, ItemStack},
+    renderer::Screen,
+    Game,
+};
+
+/// Where on the screen a block of HUD text is pinned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    BottomLeft,
+    Bottom,
+}
+
+/// Marks where `ellipsize` cut a line short.
+const ELLIPSIS: &str = "...";
+
+/// Cut a line down to `width` characters, ending it with `ELLIPSIS` if it
+/// didn't fit.
+fn ellipsize(line: &str, width: usize) -> String {
+    if line.chars().count() <= width {
+        return String::from(line);
+    }
+
+    let kept = width.saturating_sub(ELLIPSIS.len());
+
+    line.chars()
+        .take(kept)
+        .chain(ELLIPSIS.chars())
+        .take(width)
+        .collect()
+}
+
+impl Anchor {
+    /// Screen position of the first character of a line in a block of text.
+    fn position(&self, screen: &Screen, line_idx: usize, line_count: usize, width: usize) -> Coord {
+        use Anchor::*;
+
+        let (line_idx, line_count, width) = (line_idx as Dim, line_count as Dim, width as Dim);
+
+        // The screen is flipped vertically, so the last row is at the top.
+        let top = screen.rows() - 1;
+
+        let row = match self {
+            TopLeft | Top | TopRight => top - line_idx,
+            Left | Center => (screen.rows() + line_count) / 2 - 1 - line_idx,
+            BottomLeft | Bottom => line_count - 1 - line_idx,
+        };
+
+        let col = match self {
+            TopLeft | Left | BottomLeft => 0,
+            Top | Center | Bottom => (screen.cols() - width) / 2,
+            TopRight => screen.cols() - width,
+        };
+
+        Coord { row, col }
+    }
+}
+
+impl Screen {
+    /// Print lines of text pinned to an anchor, recomputed from the current
+    /// screen size so it follows terminal resizes.
+    pub fn print_anchored(&mut self, anchor: Anchor, lines: &[String]) {
+        for (idx, line) in lines.iter().enumerate() {
+            let position = anchor.position(self, idx, lines.len(), line.chars().count());
+            self.print_string(position, line);
+        }
+    }
+}
+
+impl Game {
+    /// Draws the HUD on the screen's overlay layer, above everything in the world.
+    pub fn display_hud(&self, screen: &mut Screen) {
+        let mut status = self.health_lines();
+        status.extend(self.crafting_menu_lines());
+
+        screen.print_anchored(Anchor::TopLeft, &status);
+        screen.print_anchored(Anchor::TopRight, &self.location_lines());
+        screen.print_anchored(Anchor::Top, &self.camera_lines());
+
+        let hotbar = self.hotbar_lines(screen.cols().max(0) as usize);
+        screen.print_anchored(Anchor::Bottom, &hotbar);
+        screen.print_anchored(Anchor::Center, &self.death_screen_lines());
+    }
+
+    fn health_lines(&self) -> Vec<String> {
+        let health = self
+            .find_player()
+            .and_then(|player| self.entities.healths.get(player));
+
+        let Some(health) = health else {
+            return vec![];
+        };
+
+        let text = {
+            if health.is_dead() {
+                String::from("HP: dead")
+            } else {
+                format!("HP: {:.0}/{:.0}", health.current, health.max)
+            }
+        };
+
+        vec![text]
+    }
+
+    fn location_lines(&self) -> Vec<String> {
+        let mut lines = vec![];
+
+        if let Some(body) = self.get_player_body() {
+            let position = body.tile_pos();
+
+            lines.push(format!("X: {} Y: {}", position.col, position.row));
+            lines.push(format!(
+                "Biome: {}",
+                self.terrain.biome_at(position.col).name()
+            ));
+        }
+
+        lines.push(format!("Seed: {}", self.terrain.seed()));
+        lines
+    }
+
+    fn camera_lines(&self) -> Vec<String> {
+        let mut lines = match self.camera_mode {
+            CameraMode::Free { speed, .. } => vec![format!(
+                "Free camera, speed {speed:.0} (=/- to change, V to exit)"
+            )],
+            CameraMode::FollowPlayer => vec![],
+        };
+
+        if self.camera.zoom > 1 {
+            lines.push(format!("Map x{} (M to zoom)", self.camera.zoom));
+        }
+
+        lines
+    }
+
+    /// The hotbar on a single line, with the selected slot in brackets, cut
+    /// down to `width` characters.
+    fn hotbar_lines(&self, width: usize) -> Vec<String> {
+        let Some(inventory) = self.player_inventory() else {
+            return vec![];
+        };
+
+        let slots: Vec<_> = inventory
+            .hotbar()
+            .iter()
+            .enumerate()
+            .map(|(idx, slot)| {
+                let content = match slot {
+                    Some(ItemStack {
+                        item: Item::Tool(tool),
+                        ..
+                    }) => format!("{} ({})", tool.name(), tool.durability),
+                    Some(stack) => format!("{} {}", stack.item.name(), stack.count),
+                    None => String::from("-"),
+                };
+
+                if idx == inventory.selected() {
+                    format!("[{}:{}]", idx + 1, content)
+                } else {
+                    format!(" {}:{} ", idx + 1, content)
+                }
+            })
+            .collect();
+
+        vec![ellipsize(&slots.join(""), width)]
+    }
+
+    /// Lists the recipes the player can currently craft.
+    fn crafting_menu_lines(&self) -> Vec<String> {
+        let Some(menu) = self.crafting_menu else {
+            return vec![];
+        };
+
+        let craftable = self.craftable_recipes();
+
+        let mut lines = vec![String::from(
+            "Crafting (Up/Down, Enter to craft, C to close)",
+        )];
+
+        if craftable.is_empty() {
+            lines.push(String::from("  Nothing to craft"));
+        }
+
+        for (idx, &recipe) in craftable.iter().enumerate() {
+            let marker = if idx == menu.selected { '>' } else { ' ' };
+            lines.push(format!("{marker} {}", self.recipes[recipe]));
+        }
+
+        lines
+    }
+
+    fn death_screen_lines(&self) -> Vec<String> {
+        let Some(player) = self.find_player() else {
+            return vec![];
+        };
+
+        if !self.entities.has_flags(player, EntityFlags::DEAD) {
+            return vec![];
+        }
+
+        let cause = self
+            .entities
+            .healths
+            .get(player)
+            .and_then(|health| health.last_damage);
+
+        let mut lines = vec![String::from("You died!")];
+        lines.extend(cause.map(|cause| String::from(cause.death_message())));
+        lines.push(String::from("Press R to respawn"));
+
+        lines
+    }
+}
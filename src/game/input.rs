@@ -1,6 +1,22 @@
-use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
 
-use super::Game;
+use crate::util::{Coord, Dim};
+
+use super::{
+    components::{EntityFlags, MovementControls},
+    inventory::HOTBAR_SIZE,
+    projectile::ProjectileKind,
+    Game,
+};
+
+/// Keys that throw a projectile while the debug overlay is open.
+pub const DEBUG_THROWS: [(char, ProjectileKind); 3] = [
+    ('f', ProjectileKind::Arrow),
+    ('g', ProjectileKind::MiningCharge),
+    ('b', ProjectileKind::Bomb),
+];
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Key {
@@ -14,50 +30,166 @@ impl Game {
         self.held_keys.contains(&Key { code, ctrl })
     }
 
+    /// Whether the key went down since the last tick. Ignores key repeat.
+    pub fn is_key_pressed(&self, code: KeyCode, ctrl: bool) -> bool {
+        self.pressed_keys.contains(&Key { code, ctrl })
+    }
+
+    pub fn is_button_held(&self, button: MouseButton) -> bool {
+        self.held_buttons.contains(&button)
+    }
+
+    /// Whether the mouse button went down since the last tick.
+    pub fn is_button_pressed(&self, button: MouseButton) -> bool {
+        self.pressed_buttons.contains(&button)
+    }
+
     pub fn process_event(&mut self, event: Event) {
         match event {
-            Event::Key(key_event) => {
-                if let KeyCode::Char('c') = key_event.code {
-                    if key_event.modifiers.contains(KeyModifiers::CONTROL) {
-                        self.exit_requested = true;
-                    }
-                }
-
-                let key = Key {
-                    code: key_event.code,
-                    ctrl: key_event.modifiers.contains(KeyModifiers::CONTROL),
-                };
-
-                match key_event.kind {
-                    KeyEventKind::Press => {
-                        self.held_keys.insert(key);
-                    }
-                    KeyEventKind::Release => {
-                        self.held_keys.remove(&key);
-                    }
-                    KeyEventKind::Repeat => (),
-                }
+            Event::Key(key_event) => self.process_key_event(key_event),
+            Event::Mouse(mouse_event) => self.process_mouse_event(mouse_event),
+            _ => (),
+        }
+    }
+
+    fn process_mouse_event(&mut self, mouse_event: MouseEvent) {
+        self.mouse_position = Some(Coord {
+            row: mouse_event.row as Dim,
+            col: mouse_event.column as Dim,
+        });
+
+        match mouse_event.kind {
+            MouseEventKind::Down(button) => {
+                self.held_buttons.insert(button);
+                self.pressed_buttons.insert(button);
+            }
+            MouseEventKind::Up(button) => {
+                self.held_buttons.remove(&button);
+            }
+            MouseEventKind::Drag(button) => {
+                self.held_buttons.insert(button);
             }
+            MouseEventKind::ScrollDown => self.hotbar_scroll += 1,
+            MouseEventKind::ScrollUp => self.hotbar_scroll -= 1,
             _ => (),
         }
     }
 
+    fn process_key_event(&mut self, key_event: KeyEvent) {
+        if let KeyCode::Char('c') = key_event.code {
+            if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+                self.exit_requested = true;
+            }
+        }
+
+        let typing = matches!(key_event.kind, KeyEventKind::Press | KeyEventKind::Repeat);
+
+        if self.console.open {
+            if typing {
+                self.process_console_key(key_event);
+            }
+
+            return;
+        }
+
+        if key_event.kind == KeyEventKind::Press && key_event.code == KeyCode::Char('`') {
+            self.toggle_console();
+            return;
+        }
+
+        let key = Key {
+            code: key_event.code,
+            ctrl: key_event.modifiers.contains(KeyModifiers::CONTROL),
+        };
+
+        match key_event.kind {
+            KeyEventKind::Press => {
+                self.held_keys.insert(key);
+                self.pressed_keys.insert(key);
+            }
+            KeyEventKind::Release => {
+                self.held_keys.remove(&key);
+            }
+            KeyEventKind::Repeat => (),
+        }
+    }
+
     pub fn process_player_input(&mut self) {
-        let controls = self
-            .get_player()
-            .map(|player| player.movement_controls)
-            .map(|mut controls| {
-                controls.left = self.is_key_held(KeyCode::Char('a'), false);
-                controls.right = self.is_key_held(KeyCode::Char('d'), false);
-                controls.up = self.is_key_held(KeyCode::Char('w'), false);
-                controls.down = self.is_key_held(KeyCode::Char('s'), false);
-                controls.jump = self.is_key_held(KeyCode::Char(' '), false);
-                controls
-            });
-
-        if let Some(controls) = controls {
-            self.get_player_mut()
-                .map(|player| player.movement_controls = controls);
+        if self.is_key_pressed(KeyCode::F(3), false) {
+            self.toggle_debug_overlay();
+        }
+
+        if self.is_key_pressed(KeyCode::Char('v'), false) {
+            self.toggle_free_camera();
+        }
+
+        if self.is_key_pressed(KeyCode::Char('m'), false) {
+            self.cycle_map_zoom();
+        }
+
+        let Some(player) = self.find_player() else {
+            return;
+        };
+
+        if self.entities.has_flags(player, EntityFlags::DEAD) {
+            if self.is_key_pressed(KeyCode::Char('r'), false) {
+                self.respawn_player();
+            }
+
+            return;
+        }
+
+        if self.is_key_pressed(KeyCode::Char('c'), false) {
+            self.toggle_crafting_menu();
+        }
+
+        if self.crafting_menu.is_some() || self.is_camera_free() {
+            if let Some(controls) = self.entities.movement_controls.get_mut(player) {
+                *controls = MovementControls::EMPTY;
+            }
+
+            self.process_crafting_input();
+            return;
+        }
+
+        let left = self.is_key_held(KeyCode::Char('a'), false);
+        let right = self.is_key_held(KeyCode::Char('d'), false);
+        let up = self.is_key_held(KeyCode::Char('w'), false);
+        let down = self.is_key_held(KeyCode::Char('s'), false);
+        let jump = self.is_key_held(KeyCode::Char(' '), false);
+
+        if let Some(controls) = self.entities.movement_controls.get_mut(player) {
+            controls.left = left;
+            controls.right = right;
+            controls.up = up;
+            controls.down = down;
+            controls.jump = jump;
+        }
+
+        let number_key = (1..=HOTBAR_SIZE).find(|&slot| {
+            let digit = char::from_digit(slot as u32, 10).unwrap();
+            self.is_key_pressed(KeyCode::Char(digit), false)
+        });
+
+        let scroll = std::mem::take(&mut self.hotbar_scroll);
+
+        if let Some(inventory) = self.entities.inventories.get_mut(player) {
+            if let Some(slot) = number_key {
+                inventory.select(slot - 1);
+            }
+
+            inventory.scroll(scroll);
+        }
+
+        // Throws don't cost anything, so they stay a debugging aid for now.
+        if !self.debug.overlay_enabled {
+            return;
+        }
+
+        for (key, kind) in DEBUG_THROWS {
+            if self.is_key_pressed(KeyCode::Char(key), false) {
+                self.fire_player_projectile(kind);
+            }
         }
     }
 }
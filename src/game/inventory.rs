@@ -0,0 +1,465 @@
+use crossterm::style::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::util::{Coord, CoordReal, Dim, DimReal};
+
+use super::{
+    chunk::Tile,
+    components::{Body, EntityFlags, MovementMode, Sprite},
+    console::{parse_arg_in, Command, Console},
+    entity::{EntityBuilder, EntityId},
+    physics::TIMESTEP,
+    renderer::Pixel,
+    tool::{Tool, ToolKind, ToolTier},
+    Game,
+};
+
+/// The first slots of an inventory, selectable with the number keys.
+pub const HOTBAR_SIZE: usize = 9;
+
+pub const PLAYER_INVENTORY_SIZE: usize = 36;
+
+/// How close an entity has to be to a dropped item to pick it up.
+pub const PICKUP_RANGE: DimReal = 1.5;
+
+/// Seconds before a freshly dropped item can be picked up.
+pub const PICKUP_DELAY: DimReal = 0.5;
+
+/// Seconds before a dropped item disappears.
+pub const ITEM_DESPAWN_TIME: DimReal = 300.0;
+
+/// Most items the `give` command hands out at once.
+pub const MAX_GIVE_COUNT: u32 = 999;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Item {
+    Tile(Tile),
+    Tool(Tool),
+}
+
+impl Item {
+    pub fn from_id(id: &str) -> Option<Self> {
+        Tile::from_id(id)
+            .map(Item::Tile)
+            .or_else(|| Tool::from_id(id).map(Item::Tool))
+    }
+
+    /// Whether both are the same item, ignoring how worn tools are.
+    pub fn same_kind(&self, other: &Item) -> bool {
+        match (self, other) {
+            (Item::Tile(a), Item::Tile(b)) => a == b,
+            (Item::Tool(a), Item::Tool(b)) => a.kind == b.kind && a.tier == b.tier,
+            _ => false,
+        }
+    }
+
+    pub fn max_stack(&self) -> u32 {
+        match self {
+            Item::Tile(_) => 64,
+            Item::Tool(_) => 1,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Item::Tile(tile) => tile.name(),
+            Item::Tool(tool) => tool.name(),
+        }
+    }
+
+    pub fn pixel(&self) -> Pixel {
+        match self {
+            Item::Tile(tile) => {
+                let pixel = tile.display();
+
+                Pixel {
+                    character: if pixel.character == ' ' {
+                        '#'
+                    } else {
+                        pixel.character
+                    },
+                    fg_color: pixel.bg_color.unwrap_or(pixel.fg_color),
+                    bg_color: None,
+                }
+            }
+            Item::Tool(tool) => Pixel {
+                character: match tool.kind {
+                    ToolKind::Pickaxe => '^',
+                    ToolKind::Shovel => 'l',
+                    ToolKind::Axe => 'P',
+                },
+                fg_color: match tool.tier {
+                    ToolTier::Stone => Color::DarkGrey,
+                    ToolTier::Iron => Color::White,
+                },
+                bg_color: None,
+            },
+        }
+    }
+}
+
+impl Tile {
+    /// The item left behind when the tile is mined.
+    pub fn drop_item(&self) -> Option<Item> {
+        use Tile::*;
+
+        match self {
+            Grass | Water | Lava => None,
+            GrassyDirt => Some(Item::Tile(Dirt)),
+            tile => Some(Item::Tile(*tile)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemStack {
+    pub item: Item,
+    pub count: u32,
+}
+
+impl ItemStack {
+    pub fn new(item: Item, count: u32) -> Self {
+        Self { item, count }
+    }
+}
+
+/// Serializable like `Chunk`, but nothing saves or loads inventories yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Inventory {
+    slots: Vec<Option<ItemStack>>,
+    /// Index of the selected hotbar slot.
+    selected: usize,
+}
+
+impl Inventory {
+    pub fn new(size: usize) -> Self {
+        Self {
+            slots: vec![None; size],
+            selected: 0,
+        }
+    }
+
+    pub fn hotbar(&self) -> &[Option<ItemStack>] {
+        &self.slots[..HOTBAR_SIZE.min(self.slots.len())]
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn select(&mut self, slot: usize) {
+        if slot < self.hotbar().len() {
+            self.selected = slot;
+        }
+    }
+
+    /// Move the hotbar selection by `delta` slots, wrapping around.
+    pub fn scroll(&mut self, delta: Dim) {
+        let len = self.hotbar().len() as Dim;
+
+        if len > 0 {
+            self.selected = (self.selected as Dim + delta).rem_euclid(len) as usize;
+        }
+    }
+
+    pub fn selected_stack(&self) -> Option<ItemStack> {
+        self.slots.get(self.selected).copied().flatten()
+    }
+
+    pub fn selected_stack_mut(&mut self) -> Option<&mut ItemStack> {
+        self.slots.get_mut(self.selected)?.as_mut()
+    }
+
+    /// Add the stack, topping up existing stacks first. Returns what didn't fit.
+    pub fn insert(&mut self, mut stack: ItemStack) -> Option<ItemStack> {
+        let max_stack = stack.item.max_stack();
+
+        for slot in self.slots.iter_mut().flatten() {
+            if slot.item.same_kind(&stack.item) && slot.count < max_stack {
+                let moved = stack.count.min(max_stack - slot.count);
+                slot.count += moved;
+                stack.count -= moved;
+
+                if stack.count == 0 {
+                    return None;
+                }
+            }
+        }
+
+        for slot in self.slots.iter_mut().filter(|slot| slot.is_none()) {
+            let moved = stack.count.min(max_stack);
+            *slot = Some(ItemStack::new(stack.item, moved));
+            stack.count -= moved;
+
+            if stack.count == 0 {
+                return None;
+            }
+        }
+
+        Some(stack)
+    }
+
+    /// Take up to `count` items out of the selected slot.
+    pub fn take_selected(&mut self, count: u32) -> Option<ItemStack> {
+        let slot = self.slots.get_mut(self.selected)?;
+        let stack = slot.as_mut()?;
+
+        let taken = count.min(stack.count);
+        stack.count -= taken;
+
+        let item = stack.item;
+
+        if stack.count == 0 {
+            *slot = None;
+        }
+
+        Some(ItemStack::new(item, taken))
+    }
+
+    pub fn count(&self, item: Item) -> u32 {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|stack| stack.item.same_kind(&item))
+            .map(|stack| stack.count)
+            .sum()
+    }
+
+    /// Remove `count` of the item across all slots, or nothing if there isn't enough.
+    pub fn remove(&mut self, item: Item, mut count: u32) -> bool {
+        if self.count(item) < count {
+            return false;
+        }
+
+        for slot in self.slots.iter_mut() {
+            let Some(stack) = slot.as_mut().filter(|stack| stack.item.same_kind(&item)) else {
+                continue;
+            };
+
+            let taken = count.min(stack.count);
+            stack.count -= taken;
+            count -= taken;
+
+            if stack.count == 0 {
+                *slot = None;
+            }
+
+            if count == 0 {
+                break;
+            }
+        }
+
+        true
+    }
+}
+
+/// An item lying in the world, waiting to be picked up.
+#[derive(Debug, Clone, Copy)]
+pub struct ItemDrop {
+    pub stack: ItemStack,
+    pub pickup_delay: DimReal,
+    pub lifetime: DimReal,
+}
+
+pub fn new_item_drop(stack: ItemStack, position: CoordReal) -> EntityBuilder {
+    let mut body = Body::new(position);
+    body.mass = 1.0;
+
+    EntityBuilder::new()
+        .with_body(body)
+        .with_sprite(Sprite::still(stack.item.pixel()))
+        .with_movement_mode(MovementMode::Dynamic)
+        .with_item_drop(ItemDrop {
+            stack,
+            pickup_delay: PICKUP_DELAY,
+            lifetime: ITEM_DESPAWN_TIME,
+        })
+        .with_flags(EntityFlags::COLLECTIBLE)
+}
+
+impl Game {
+    pub fn drop_item(&mut self, stack: ItemStack, pos: Coord) -> EntityId {
+        self.spawn(new_item_drop(stack, pos.to_real()))
+    }
+
+    /// Age dropped items and move them into the inventories of entities touching them.
+    pub fn collect_item_drops(&mut self) {
+        let entities = &mut self.entities;
+        let mut collected = vec![];
+
+        for (id, drop) in entities.item_drops.iter_mut() {
+            drop.pickup_delay -= TIMESTEP;
+            drop.lifetime -= TIMESTEP;
+
+            if drop.lifetime <= 0.0 {
+                collected.push(id);
+                continue;
+            }
+
+            let flags = entities.flags.get(id).copied().unwrap_or_default();
+
+            if drop.pickup_delay > 0.0 || !flags.contains(EntityFlags::COLLECTIBLE) {
+                continue;
+            }
+
+            let Some(position) = entities.bodies.get(id).map(|body| body.position) else {
+                continue;
+            };
+
+            for (collector, inventory) in entities.inventories.iter_mut() {
+                if entities
+                    .flags
+                    .get(collector)
+                    .copied()
+                    .unwrap_or_default()
+                    .contains(EntityFlags::DEAD)
+                {
+                    continue;
+                }
+
+                let in_range = match entities.bodies.get(collector) {
+                    Some(body) => (body.position - position).length() <= PICKUP_RANGE,
+                    None => false,
+                };
+
+                if !in_range {
+                    continue;
+                }
+
+                match inventory.insert(drop.stack) {
+                    Some(leftover) => drop.stack = leftover,
+                    None => {
+                        collected.push(id);
+                        break;
+                    }
+                }
+            }
+        }
+
+        for id in collected {
+            self.entities.mark_for_deletion(id);
+        }
+    }
+
+    pub fn player_inventory(&self) -> Option<&Inventory> {
+        self.entities.inventories.get(self.find_player()?)
+    }
+
+    pub fn player_inventory_mut(&mut self) -> Option<&mut Inventory> {
+        let player = self.find_player()?;
+        self.entities.inventories.get_mut(player)
+    }
+}
+
+/// Ids of every item that can be named in commands and data files.
+pub fn item_ids() -> Vec<String> {
+    let tiles = Tile::NAMED.iter().map(|tile| tile.id());
+    let tools = Tool::all().map(|tool| tool.id());
+
+    tiles.chain(tools).map(String::from).collect()
+}
+
+pub fn register_commands(console: &mut Console) {
+    console.register(Command {
+        name: "give",
+        usage: "give <item> [count]",
+        run: |game, args| {
+            let id = args.first().ok_or("Missing <item>")?;
+            let item = Item::from_id(id).ok_or_else(|| format!("Unknown item `{id}`"))?;
+            let count: u32 = match args.get(1) {
+                Some(_) => parse_arg_in(args, 1, "count", 1..=MAX_GIVE_COUNT)?,
+                None => 1,
+            };
+
+            let player = game.find_player().ok_or("No player")?;
+            let position = game.get_player_body().ok_or("No player")?.tile_pos();
+
+            // Hand out tools one at a time since they don't stack.
+            let mut leftovers = vec![];
+            let mut remaining = count;
+
+            while remaining > 0 {
+                let amount = remaining.min(item.max_stack());
+                remaining -= amount;
+
+                let inventory = game
+                    .entities
+                    .inventories
+                    .get_mut(player)
+                    .ok_or("No inventory")?;
+                leftovers.extend(inventory.insert(ItemStack::new(item, amount)));
+            }
+
+            for stack in leftovers {
+                game.drop_item(stack, position);
+            }
+
+            Ok(format!("Gave {count} {}", item.name()))
+        },
+        complete: |idx| match idx {
+            0 => item_ids(),
+            _ => vec![],
+        },
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dirt(count: u32) -> ItemStack {
+        ItemStack::new(Item::Tile(Tile::Dirt), count)
+    }
+
+    #[test]
+    fn insert_tops_up_stacks_before_filling_empty_slots() {
+        let mut inventory = Inventory::new(3);
+
+        assert_eq!(inventory.insert(dirt(40)), None);
+        assert_eq!(inventory.insert(dirt(40)), None);
+        assert_eq!(inventory.hotbar(), [Some(dirt(64)), Some(dirt(16)), None]);
+
+        assert_eq!(inventory.insert(dirt(150)), Some(dirt(38)));
+        assert_eq!(inventory.count(Item::Tile(Tile::Dirt)), 192);
+    }
+
+    #[test]
+    fn worn_tools_count_as_the_same_item() {
+        let fresh = Tool::new(ToolKind::Pickaxe, ToolTier::Stone);
+        let worn = Tool {
+            durability: 3,
+            ..fresh
+        };
+
+        let mut inventory = Inventory::new(3);
+        inventory.insert(ItemStack::new(Item::Tool(worn), 1));
+
+        assert_eq!(inventory.count(Item::Tool(fresh)), 1);
+        assert!(inventory.remove(Item::Tool(fresh), 1));
+        assert_eq!(inventory.count(Item::Tool(fresh)), 0);
+
+        let iron = Tool::new(ToolKind::Pickaxe, ToolTier::Iron);
+        inventory.insert(ItemStack::new(Item::Tool(iron), 1));
+
+        assert!(!inventory.remove(Item::Tool(fresh), 1));
+    }
+
+    #[test]
+    fn round_trips_through_serde() {
+        let worn = Tool {
+            durability: 3,
+            ..Tool::new(ToolKind::Axe, ToolTier::Iron)
+        };
+
+        let mut inventory = Inventory::new(PLAYER_INVENTORY_SIZE);
+        inventory.insert(dirt(100));
+        inventory.insert(ItemStack::new(Item::Tool(worn), 1));
+        inventory.select(2);
+
+        let json = serde_json::to_string(&inventory).unwrap();
+        let loaded: Inventory = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded.slots, inventory.slots);
+        assert_eq!(loaded.selected(), 2);
+    }
+}
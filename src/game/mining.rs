@@ -0,0 +1,217 @@
+use crossterm::event::MouseButton;
+
+use crate::util::{Coord, DimReal};
+
+use super::{
+    chunk::Tile,
+    components::EntityFlags,
+    inventory::{Item, ItemStack},
+    physics::TIMESTEP,
+    renderer::Screen,
+    tool::Tool,
+    Game,
+};
+
+/// How far from the player tiles can be mined or placed.
+pub const MINING_REACH: DimReal = 5.0;
+
+/// Durability removed per second of mining by hand or with the wrong tool.
+pub const MINING_SPEED: DimReal = 2.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct MiningProgress {
+    pub target: Coord,
+    /// Durability removed so far.
+    pub progress: DimReal,
+}
+
+impl MiningProgress {
+    /// How close the target is to breaking, from 0.0 to 1.0.
+    pub fn fraction(&self, tile: &Tile) -> DimReal {
+        (self.progress / tile.durability()).min(1.0)
+    }
+}
+
+impl Game {
+    /// World tile under the mouse cursor.
+    pub fn cursor_world_position(&self, screen: &Screen) -> Option<Coord> {
+        // Cells cover several tiles on the map, so there's no single tile to point at.
+        if self.camera.zoom > 1 {
+            return None;
+        }
+
+        let terminal_point = self.mouse_position?;
+        let screen_point = screen.terminal_to_screen(terminal_point);
+
+        Some(self.camera.unproject(screen_point, screen))
+    }
+
+    fn in_reach(&self, pos: Coord) -> bool {
+        match self.get_player_body() {
+            Some(body) => (pos.to_real() - body.position).length() <= MINING_REACH,
+            None => false,
+        }
+    }
+
+    /// Mine the tile under the cursor while the left button is held and place
+    /// the selected tile on right click.
+    pub fn update_mining(&mut self, screen: &Screen) {
+        let Some(target) = self
+            .cursor_world_position(screen)
+            .filter(|&pos| self.in_reach(pos) && self.player_can_interact())
+        else {
+            self.mining = None;
+            return;
+        };
+
+        if self.is_button_held(MouseButton::Left) {
+            let drop = self.mine(target).and_then(|tile| tile.drop_item());
+
+            if let Some(item) = drop {
+                self.drop_item(ItemStack::new(item, 1), target);
+            }
+        } else {
+            self.mining = None;
+        }
+
+        if self.is_button_pressed(MouseButton::Right) {
+            self.place_selected_tile(target);
+        }
+    }
+
+    fn player_can_interact(&self) -> bool {
+        match self.find_player() {
+            Some(player) => !self.entities.has_flags(player, EntityFlags::DEAD),
+            None => false,
+        }
+    }
+
+    /// Advance mining on `target`. Returns the tile once it breaks.
+    fn mine(&mut self, target: Coord) -> Option<Tile> {
+        let tile = self.terrain[target].filter(|tile| tile.fluid().is_none());
+
+        let Some(tile) = tile else {
+            self.mining = None;
+            return None;
+        };
+
+        let Some(speed) = self.player_mining_speed(&tile) else {
+            self.mining = None;
+            return None;
+        };
+
+        let mining = match &mut self.mining {
+            Some(mining) if mining.target == target => mining,
+            mining => mining.insert(MiningProgress {
+                target,
+                progress: 0.0,
+            }),
+        };
+
+        mining.progress += speed * TIMESTEP;
+
+        if mining.progress < tile.durability() {
+            return None;
+        }
+
+        self.mining = None;
+        self.terrain.set_tile(target, None);
+        self.wear_player_tool(&tile);
+
+        Some(tile)
+    }
+
+    fn player_tool(&self) -> Option<Tool> {
+        match self.player_inventory()?.selected_stack()?.item {
+            Item::Tool(tool) => Some(tool),
+            _ => None,
+        }
+    }
+
+    /// Durability per second the player removes from the tile with the held
+    /// tool, or `None` if the tile needs a better tool.
+    fn player_mining_speed(&self, tile: &Tile) -> Option<DimReal> {
+        let tool = self.player_tool().filter(|tool| tool.is_suited_for(tile));
+
+        match (tool, tile.required_tier()) {
+            (Some(tool), _) => Some(MINING_SPEED * tool.tier.speed()),
+            (None, Some(_)) => None,
+            (None, None) => Some(MINING_SPEED),
+        }
+    }
+
+    /// Use up the held tool if it was the right one for the mined tile.
+    fn wear_player_tool(&mut self, tile: &Tile) {
+        let Some(inventory) = self.player_inventory_mut() else {
+            return;
+        };
+
+        let Some(stack) = inventory.selected_stack_mut() else {
+            return;
+        };
+
+        let Item::Tool(tool) = &mut stack.item else {
+            return;
+        };
+
+        if !tool.is_suited_for(tile) {
+            return;
+        }
+
+        tool.durability = tool.durability.saturating_sub(1);
+
+        if tool.durability == 0 {
+            inventory.take_selected(1);
+        }
+    }
+
+    /// Place one tile from the player's selected hotbar slot.
+    fn place_selected_tile(&mut self, pos: Coord) {
+        let Some(ItemStack {
+            item: Item::Tile(tile),
+            ..
+        }) = self
+            .player_inventory()
+            .and_then(|inventory| inventory.selected_stack())
+        else {
+            return;
+        };
+
+        if self.place_tile(pos, tile) {
+            if let Some(inventory) = self.player_inventory_mut() {
+                inventory.take_selected(1);
+            }
+        }
+    }
+
+    /// Put a tile into an empty or flooded cell next to existing terrain,
+    /// as long as no living entity stands there.
+    pub fn place_tile(&mut self, pos: Coord, tile: Tile) -> bool {
+        let replaceable = match self.terrain[pos] {
+            Some(existing) => existing.fluid().is_some(),
+            None => self.terrain.contains(pos),
+        };
+
+        let supported = [Coord::UP, Coord::DOWN, Coord::LEFT, Coord::RIGHT]
+            .into_iter()
+            .any(
+                |offset| matches!(self.terrain[pos + offset], Some(tile) if tile.fluid().is_none()),
+            );
+
+        let occupied = self
+            .entities
+            .healths
+            .ids()
+            .any(|id| match self.entities.bodies.get(id) {
+                Some(body) => body.tile_pos() == pos,
+                None => false,
+            });
+
+        if !replaceable || !supported || occupied {
+            return false;
+        }
+
+        self.terrain.set_tile(pos, Some(tile));
+        true
+    }
+}
@@ -0,0 +1,336 @@
+use crossterm::style::Color;
+use rand::Rng;
+
+use crate::util::{Coord, Dim, DimReal};
+
+use super::{
+    chunk::CHUNK_HEIGHT,
+    components::{
+        Ai, AiState, Body, EntityFlags, Health, MovementControls, MovementMode, Sprite,
+        WalkingParams,
+    },
+    console::{parse_arg_in, Command, Console},
+    entity::EntityBuilder,
+    health::DamageSource,
+    pathfinding::{jump_height, Navigation},
+    physics::TIMESTEP,
+    renderer::Pixel,
+    terrain::Terrain,
+    Game,
+};
+
+/// Seconds a mob spends running away after getting hurt.
+pub const FLEE_TIME: DimReal = 3.0;
+
+/// Hostile mobs give up the chase past `sight_range` times this.
+pub const CHASE_GIVE_UP_FACTOR: DimReal = 1.5;
+
+/// How close a hostile mob has to be to hit the player.
+pub const ATTACK_RANGE: DimReal = 1.0;
+
+/// Most mobs the `spawn` command creates at once.
+pub const MAX_SPAWN_COUNT: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MobKind {
+    Sheep,
+    Zombie,
+}
+
+impl MobKind {
+    pub const ALL: [Self; 2] = [Self::Sheep, Self::Zombie];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sheep => "sheep",
+            Self::Zombie => "zombie",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    pub fn is_hostile(&self) -> bool {
+        matches!(self, Self::Zombie)
+    }
+}
+
+pub fn new_mob(kind: MobKind, position: Coord) -> EntityBuilder {
+    let (pixel, max_speed, health, ai) = match kind {
+        MobKind::Sheep => (
+            Pixel {
+                character: 'S',
+                fg_color: Color::White,
+                bg_color: None,
+            },
+            4.0,
+            30.0,
+            Ai::new(0.0, 0.0),
+        ),
+        MobKind::Zombie => (
+            Pixel {
+                character: 'Z',
+                fg_color: Color::DarkGreen,
+                bg_color: None,
+            },
+            5.5,
+            50.0,
+            Ai::new(24.0, 10.0),
+        ),
+    };
+
+    let mut body = Body::new(position.to_real());
+    body.mass = 40.0;
+
+    let params = WalkingParams {
+        max_speed,
+        ..Default::default()
+    };
+
+    let flags = {
+        if kind.is_hostile() {
+            EntityFlags::HOSTILE
+        } else {
+            EntityFlags::empty()
+        }
+    };
+
+    EntityBuilder::new()
+        .with_body(body)
+        .with_sprite(Sprite::still(pixel))
+        .with_movement_mode(MovementMode::Walking(params))
+        .with_health(Health::new(health))
+        .with_ai(ai)
+        .with_navigation(Navigation::default())
+        .with_flags(flags)
+}
+
+impl Game {
+    /// Advance every mob's state machine and steer it accordingly.
+    pub fn update_ai(&mut self) {
+        let player_position = self
+            .find_player()
+            .filter(|&player| !self.entities.has_flags(player, EntityFlags::DEAD))
+            .and_then(|player| self.entities.bodies.get(player))
+            .map(|body| body.position);
+
+        let player_ground = player_position.and_then(|position| {
+            let tile_pos = Coord {
+                row: position.row.round() as Dim,
+                col: position.col.round() as Dim,
+            };
+
+            self.terrain.ground_below(tile_pos, CHUNK_HEIGHT as Dim)
+        });
+
+        let mut rng = rand::thread_rng();
+        let entities = &mut self.entities;
+
+        for (id, ai) in entities.ais.iter_mut() {
+            let Some(body) = entities.bodies.get(id) else {
+                continue;
+            };
+
+            let flags = entities.flags.get(id).copied().unwrap_or_default();
+            let hostile = flags.contains(EntityFlags::HOSTILE);
+
+            let hurt = match entities.healths.get(id) {
+                Some(health) => health.invulnerability_timer > 0.0,
+                None => false,
+            };
+
+            let to_player = player_position.map(|position| position - body.position);
+            let player_distance = to_player.map(|offset| offset.length());
+
+            ai.state_timer -= TIMESTEP;
+            ai.state = next_state(ai, hostile, hurt, player_distance, &mut rng);
+
+            let path_controls = match (ai.state, player_ground) {
+                (AiState::Chase, Some(goal)) => {
+                    let jump_height = match entities.movement_modes.get(id) {
+                        Some(MovementMode::Walking(params)) => {
+                            jump_height(params, -self.gravity.row * body.gravity_scale)
+                        }
+                        _ => 0,
+                    };
+
+                    entities.navigations.get_mut(id).and_then(|navigation| {
+                        navigate(navigation, &self.terrain, body, flags, goal, jump_height)
+                    })
+                }
+                _ => None,
+            };
+
+            let Some(controls) = entities.movement_controls.get_mut(id) else {
+                continue;
+            };
+
+            let direction = match (ai.state, to_player) {
+                (AiState::Idle, _) => 0.0,
+                (AiState::Wander { direction }, _) => direction,
+                (AiState::Chase, Some(offset)) if offset.col.abs() > 0.5 => offset.col.signum(),
+                (AiState::Flee, Some(offset)) => -offset.col.signum(),
+                _ => 0.0,
+            };
+
+            let intent = path_controls.unwrap_or(MovementControls {
+                left: direction < 0.0,
+                right: direction > 0.0,
+                ..MovementControls::EMPTY
+            });
+
+            steer(controls, intent, flags);
+        }
+    }
+
+    /// Hostile mobs hurt the player on contact.
+    pub fn perform_mob_attacks(&mut self) {
+        let Some(player) = self.find_player() else {
+            return;
+        };
+
+        let Some(player_position) = self.entities.bodies.get(player).map(|body| body.position)
+        else {
+            return;
+        };
+
+        let mut attacks = vec![];
+
+        for (id, ai) in self.entities.ais.iter() {
+            if ai.state != AiState::Chase || !self.entities.has_flags(id, EntityFlags::HOSTILE) {
+                continue;
+            }
+
+            let Some(body) = self.entities.bodies.get(id) else {
+                continue;
+            };
+
+            if (player_position - body.position).length() <= ATTACK_RANGE {
+                attacks.push((id, ai.attack_damage));
+            }
+        }
+
+        for (attacker, damage) in attacks {
+            self.deal_damage(player, damage, DamageSource::Entity(attacker));
+        }
+    }
+}
+
+fn next_state(
+    ai: &mut Ai,
+    hostile: bool,
+    hurt: bool,
+    player_distance: Option<DimReal>,
+    rng: &mut impl Rng,
+) -> AiState {
+    if hurt && ai.state != AiState::Flee {
+        ai.state_timer = FLEE_TIME;
+        return AiState::Flee;
+    }
+
+    if ai.state == AiState::Flee && ai.state_timer > 0.0 {
+        return AiState::Flee;
+    }
+
+    if hostile {
+        match player_distance {
+            Some(distance) if distance <= ai.sight_range => return AiState::Chase,
+            Some(distance)
+                if ai.state == AiState::Chase
+                    && distance <= ai.sight_range * CHASE_GIVE_UP_FACTOR =>
+            {
+                return AiState::Chase
+            }
+            _ => (),
+        }
+    }
+
+    match ai.state {
+        AiState::Idle | AiState::Wander { .. } if ai.state_timer > 0.0 => ai.state,
+        AiState::Idle => {
+            ai.state_timer = rng.gen_range(1.0..4.0);
+
+            let direction = if rng.gen() { 1.0 } else { -1.0 };
+            AiState::Wander { direction }
+        }
+        _ => {
+            ai.state_timer = rng.gen_range(1.0..3.0);
+            AiState::Idle
+        }
+    }
+}
+
+/// Follow the cached path to `goal`, recomputing it when it gets old or the
+/// goal moves away from it. Paths are only planned from solid footing.
+fn navigate(
+    navigation: &mut Navigation,
+    terrain: &Terrain,
+    body: &Body,
+    flags: EntityFlags,
+    goal: Coord,
+    jump_height: Dim,
+) -> Option<MovementControls> {
+    let position = body.tile_pos();
+
+    navigation.repath_timer -= TIMESTEP;
+
+    let grounded =
+        flags.intersects(EntityFlags::ON_GROUND | EntityFlags::CLIMBING | EntityFlags::IN_FLUID);
+
+    if grounded && navigation.needs_repath(goal) {
+        let steps = terrain
+            .find_path(position, goal, jump_height)
+            .unwrap_or_default();
+        navigation.set_path(goal, steps);
+    }
+
+    navigation.follow(position)
+}
+
+fn steer(controls: &mut MovementControls, intent: MovementControls, flags: EntityFlags) {
+    let moving = intent.horizontal_direction() != 0.0;
+
+    // Hop over obstacles and keep afloat in fluids.
+    let blocked = moving && flags.contains(EntityFlags::TOUCHING_WALL);
+    let swimming = moving && flags.contains(EntityFlags::IN_FLUID);
+
+    let wants_jump = intent.jump || blocked || swimming;
+
+    // Jumps only trigger on a fresh press, so let go for a tick after landing.
+    let held_through_landing = controls.jump && flags.contains(EntityFlags::ON_GROUND);
+
+    *controls = intent;
+    controls.jump = wants_jump && (swimming || !held_through_landing);
+}
+
+pub fn register_commands(console: &mut Console) {
+    console.register(Command {
+        name: "spawn",
+        usage: "spawn <mob> [count]",
+        run: |game, args| {
+            let name = args.first().ok_or("Missing <mob>")?;
+            let kind = MobKind::from_name(name).ok_or_else(|| format!("Unknown mob `{name}`"))?;
+            let count: usize = match args.get(1) {
+                Some(_) => parse_arg_in(args, 1, "count", 1..=MAX_SPAWN_COUNT)?,
+                None => 1,
+            };
+
+            let body = game.get_player_body().ok_or("No player")?;
+            let position = body.tile_pos() + Coord { row: 1, col: 2 };
+
+            for _ in 0..count {
+                game.spawn(new_mob(kind, position));
+            }
+
+            Ok(format!("Spawned {count} {name}"))
+        },
+        complete: |idx| match idx {
+            0 => MobKind::ALL
+                .iter()
+                .map(|kind| kind.name().to_string())
+                .collect(),
+            _ => vec![],
+        },
+    });
+}
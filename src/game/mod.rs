@@ -1,32 +1,83 @@
 use std::collections::HashSet;
 
-use crate::util::{Coord, CoordReal};
+use crossterm::event::MouseButton;
+
+use crate::util::{Coord, CoordReal, Dim};
 
 use self::{
-    entity::{Entity, EntityFlag},
+    camera::{CameraController, CameraMode, CameraShake},
+    components::EntityFlags,
+    console::Console,
+    crafting::{parse_recipes, CraftingMenu, Recipe, RECIPES_SOURCE},
+    debug::{DebugStats, TickPhase, TickTimings},
+    entity::{Entities, EntityBuilder, EntityId},
+    explosion::Explosion,
+    health::DamageEvent,
     input::Key,
+    mining::MiningProgress,
     renderer::{Camera, Screen},
     terrain::Terrain,
 };
 
 pub mod anim;
+pub mod biome;
+pub mod camera;
 pub mod chunk;
+pub mod components;
+pub mod console;
+pub mod crafting;
+pub mod debug;
 pub mod display;
 pub mod entity;
+pub mod explosion;
+pub mod falling;
+pub mod fluid;
+pub mod health;
+pub mod hud;
 pub mod input;
+pub mod inventory;
+pub mod mining;
+pub mod mob;
+pub mod pathfinding;
 pub mod physics;
 pub mod player;
+pub mod projectile;
 pub mod renderer;
+pub mod spawn;
+pub mod spawner;
 pub mod terrain;
+pub mod time;
+pub mod tool;
 pub mod update;
 
 pub struct Game {
     exit_requested: bool,
     camera: Camera,
-    entities: Vec<Entity>,
+    camera_mode: CameraMode,
+    camera_controller: CameraController,
+    camera_shake: CameraShake,
+    entities: Entities,
     terrain: Terrain,
     gravity: CoordReal,
     held_keys: HashSet<Key>,
+    pressed_keys: HashSet<Key>,
+    /// Terminal cell the mouse was last seen over.
+    mouse_position: Option<Coord>,
+    held_buttons: HashSet<MouseButton>,
+    pressed_buttons: HashSet<MouseButton>,
+    mining: Option<MiningProgress>,
+    /// Hotbar scrolling accumulated from the mouse wheel since the last tick.
+    hotbar_scroll: Dim,
+    recipes: Vec<Recipe>,
+    crafting_menu: Option<CraftingMenu>,
+    debug: DebugStats,
+    console: Console,
+    tick_count: u64,
+    damage_events: Vec<DamageEvent>,
+    /// Set off by destroyed explosive tiles, waiting for `detonate_explosions`.
+    pending_explosions: Vec<Explosion>,
+    spawn_point: Option<Coord>,
+    time: u64,
 }
 
 impl Game {
@@ -35,14 +86,34 @@ impl Game {
             exit_requested: false,
             camera: Camera {
                 position: Coord::ZERO,
+                zoom: 1,
             },
-            entities: vec![],
+            camera_mode: CameraMode::FollowPlayer,
+            camera_controller: CameraController::default(),
+            camera_shake: CameraShake::default(),
+            entities: Entities::new(),
             terrain: Terrain::new(),
             gravity: CoordReal {
                 row: -10.0,
                 col: 0.0,
             },
             held_keys: HashSet::new(),
+            pressed_keys: HashSet::new(),
+            mouse_position: None,
+            held_buttons: HashSet::new(),
+            pressed_buttons: HashSet::new(),
+            mining: None,
+            hotbar_scroll: 0,
+            recipes: parse_recipes(RECIPES_SOURCE)
+                .unwrap_or_else(|error| panic!("Invalid data/recipes.txt, {error}")),
+            crafting_menu: None,
+            debug: DebugStats::default(),
+            console: new_console(),
+            tick_count: 0,
+            damage_events: vec![],
+            pending_explosions: vec![],
+            spawn_point: None,
+            time: 0,
         }
     }
 
@@ -50,42 +121,90 @@ impl Game {
         self.exit_requested
     }
 
-    pub fn spawn(&mut self, entity: Entity) {
-        let is_player = entity.flags.contains(&EntityFlag::Player);
+    pub fn spawn(&mut self, builder: EntityBuilder) -> EntityId {
+        let is_player = builder.has_flags(EntityFlags::PLAYER);
 
-        self.entities.push(entity);
+        let id = self.entities.spawn(builder);
 
         if is_player {
             self.snap_camera_to_player();
             self.load_chunks_around_camera();
         }
+
+        id
     }
 
     pub fn tick(&mut self, screen: &mut Screen) {
+        self.debug.record_frame();
+        let mut timings = TickTimings::start();
+
         self.process_player_input();
+        self.update_mining(screen);
+        timings.lap(TickPhase::Input);
+
         self.update_physics_bodies();
+        timings.lap(TickPhase::Physics);
+
         self.solve_collisions();
+        self.update_projectiles();
+        self.settle_falling_blocks();
+        self.detonate_explosions();
+        timings.lap(TickPhase::Collisions);
+
+        self.collect_item_drops();
+        self.apply_damage();
+        self.update_spawn_point();
         self.update_entities_state();
-        self.snap_camera_to_player();
+        self.spawn_mobs(screen);
+        self.despawn_far_mobs(screen);
+        self.update_fluids();
+        self.drop_unsupported_blocks();
+        timings.lap(TickPhase::Update);
+
+        self.update_camera(screen);
         self.load_chunks_around_camera();
+        timings.lap(TickPhase::ChunkLoading);
+
         self.delete_marked_entities();
+        timings.lap(TickPhase::Update);
+
         self.display_terrain(screen);
         self.display_entities(screen);
+        self.display_mining_cursor(screen);
+        self.display_hud(screen);
+        self.display_debug_overlay(screen);
+        self.display_console(screen);
+        timings.lap(TickPhase::Display);
+
+        self.debug.timings = timings;
+
+        self.pressed_keys.clear();
+        self.pressed_buttons.clear();
+        self.advance_time();
+        self.tick_count += 1;
     }
 
     pub fn delete_marked_entities(&mut self) {
-        let mut i = 0;
-
-        while i < self.entities.len() {
-            if self.entities[i].deletion_flag {
-                self.entities.remove(i);
-            } else {
-                i += 1;
-            }
-        }
+        self.entities.delete_marked();
     }
 }
 
+/// A console with the commands of every subsystem registered.
+fn new_console() -> Console {
+    let mut console = Console::new();
+
+    camera::register_commands(&mut console);
+    console::register_commands(&mut console);
+    explosion::register_commands(&mut console);
+    inventory::register_commands(&mut console);
+    mob::register_commands(&mut console);
+    physics::register_commands(&mut console);
+    terrain::register_commands(&mut console);
+    time::register_commands(&mut console);
+
+    console
+}
+
 impl Default for Game {
     fn default() -> Self {
         Self::new()
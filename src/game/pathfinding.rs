@@ -0,0 +1,387 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+use crate::util::{Coord, Dim, DimReal};
+
+use super::{
+    components::{MovementControls, WalkingParams},
+    terrain::Terrain,
+};
+
+/// Upper bound on the nodes visited by a single search.
+pub const MAX_PATH_EXPANSIONS: usize = 4096;
+
+/// Deepest drop a walking entity will plan to take.
+pub const MAX_PLANNED_DROP: Dim = 12;
+
+/// Seconds before a path is recomputed even if the goal hasn't moved.
+/// Failed searches are also kept for this long.
+pub const REPATH_INTERVAL: DimReal = 1.0;
+
+/// Tiles the goal can move along either axis before the path is recomputed early.
+pub const REPATH_GOAL_TOLERANCE: Dim = 3;
+
+/// One leg of a path: hold `controls` until standing on `target`.
+#[derive(Debug, Clone, Copy)]
+pub struct PathStep {
+    pub target: Coord,
+    pub controls: MovementControls,
+}
+
+/// A path cached for the goal it was computed for. An empty path means the
+/// goal couldn't be reached.
+#[derive(Debug, Clone, Default)]
+pub struct Navigation {
+    pub goal: Option<Coord>,
+    pub steps: Vec<PathStep>,
+    pub next_step: usize,
+    pub repath_timer: DimReal,
+}
+
+impl Navigation {
+    /// Controls to apply while standing on `position`, or `None` if the path
+    /// is exhausted or was lost.
+    pub fn follow(&mut self, position: Coord) -> Option<MovementControls> {
+        let remaining = self.steps.get(self.next_step..)?;
+
+        // Skip ahead if a later waypoint was reached early (e.g. by falling).
+        if let Some(idx) = remaining.iter().position(|step| step.target == position) {
+            self.next_step += idx + 1;
+        }
+
+        self.steps.get(self.next_step).map(|step| step.controls)
+    }
+
+    pub fn needs_repath(&self, goal: Coord) -> bool {
+        let goal_moved = match self.goal {
+            Some(old_goal) => {
+                let offset = goal - old_goal;
+                offset.row.abs().max(offset.col.abs()) > REPATH_GOAL_TOLERANCE
+            }
+            None => true,
+        };
+
+        goal_moved || self.repath_timer <= 0.0
+    }
+
+    pub fn set_path(&mut self, goal: Coord, steps: Vec<PathStep>) {
+        self.goal = Some(goal);
+        self.steps = steps;
+        self.next_step = 0;
+        self.repath_timer = REPATH_INTERVAL;
+    }
+}
+
+/// Highest ledge in tiles a walker can jump onto under the given gravity.
+pub fn jump_height(params: &WalkingParams, gravity: DimReal) -> Dim {
+    if gravity <= 0.0 {
+        return 0;
+    }
+
+    let height = params.jump_impulse * params.jump_impulse / (2.0 * gravity);
+    height.floor() as Dim
+}
+
+fn walk_controls(direction: Dim) -> MovementControls {
+    MovementControls {
+        left: direction < 0,
+        right: direction > 0,
+        ..MovementControls::EMPTY
+    }
+}
+
+impl Terrain {
+    pub fn can_occupy(&self, pos: Coord) -> bool {
+        match self[pos] {
+            Some(tile) => !tile.is_impassable(),
+            None => self.contains(pos),
+        }
+    }
+
+    /// Whether a walker can rest at the position: on solid ground, a
+    /// platform, a climbable tile, or in a fluid.
+    pub fn can_stand(&self, pos: Coord) -> bool {
+        if !self.can_occupy(pos) {
+            return false;
+        }
+
+        let held_up = match self[pos] {
+            Some(tile) => tile.is_climbable() || tile.fluid().is_some(),
+            None => false,
+        };
+
+        let supported = match self[pos + Coord::DOWN] {
+            Some(below) => below.is_impassable() || below.is_one_way(),
+            None => false,
+        };
+
+        held_up || supported
+    }
+
+    /// Where an entity at `pos` ends up after falling, if it's a safe drop.
+    pub fn ground_below(&self, pos: Coord, max_drop: Dim) -> Option<Coord> {
+        (0..=max_drop)
+            .map(|drop| pos + Coord { row: -drop, col: 0 })
+            .take_while(|&pos| self.can_occupy(pos))
+            .find(|&pos| self.can_stand(pos))
+    }
+
+    fn path_neighbors(&self, pos: Coord, jump_height: Dim) -> Vec<(Coord, u32, MovementControls)> {
+        let mut neighbors = vec![];
+
+        for direction in [-1, 1] {
+            let side = pos
+                + Coord {
+                    row: 0,
+                    col: direction,
+                };
+
+            if self.can_occupy(side) {
+                if self.can_stand(side) {
+                    neighbors.push((side, 1, walk_controls(direction)));
+                } else if let Some(landing) = self.ground_below(side, MAX_PLANNED_DROP) {
+                    let drop = (side.row - landing.row) as u32;
+                    neighbors.push((landing, 1 + drop, walk_controls(direction)));
+                }
+            }
+
+            for height in 1..=jump_height {
+                let headroom = pos
+                    + Coord {
+                        row: height,
+                        col: 0,
+                    };
+
+                if !self.can_occupy(headroom) {
+                    break;
+                }
+
+                let ledge = headroom
+                    + Coord {
+                        row: 0,
+                        col: direction,
+                    };
+
+                if self.can_stand(ledge) {
+                    let controls = MovementControls {
+                        jump: true,
+                        ..walk_controls(direction)
+                    };
+
+                    neighbors.push((ledge, 2 + height as u32, controls));
+                    break;
+                }
+            }
+        }
+
+        let climbable = matches!(self[pos], Some(tile) if tile.is_climbable());
+        let swimming = matches!(self[pos], Some(tile) if tile.fluid().is_some());
+
+        if climbable || swimming {
+            let above = pos + Coord::UP;
+
+            if self.can_stand(above) {
+                let controls = MovementControls {
+                    up: climbable,
+                    jump: swimming,
+                    ..MovementControls::EMPTY
+                };
+
+                neighbors.push((above, 1, controls));
+            }
+
+            let below = pos + Coord::DOWN;
+
+            if self.can_stand(below) {
+                let controls = MovementControls {
+                    down: true,
+                    ..MovementControls::EMPTY
+                };
+
+                neighbors.push((below, 1, controls));
+            }
+        }
+
+        neighbors
+    }
+
+    /// A* search over loaded tiles for a walker that can jump `jump_height`
+    /// tiles high. Returns `None` if the goal is unreachable or too far.
+    pub fn find_path(&self, start: Coord, goal: Coord, jump_height: Dim) -> Option<Vec<PathStep>> {
+        let heuristic =
+            |pos: Coord| ((pos.row - goal.row).abs() + (pos.col - goal.col).abs()) as u32;
+
+        let mut open = BinaryHeap::new();
+        let mut costs = HashMap::new();
+        let mut came_from: HashMap<Coord, (Coord, MovementControls)> = HashMap::new();
+
+        open.push(Reverse((heuristic(start), start)));
+        costs.insert(start, 0);
+
+        let mut expansions = 0;
+
+        while let Some(Reverse((_, pos))) = open.pop() {
+            if pos == goal {
+                let mut steps = vec![];
+                let mut current = pos;
+
+                while let Some(&(previous, controls)) = came_from.get(&current) {
+                    steps.push(PathStep {
+                        target: current,
+                        controls,
+                    });
+
+                    current = previous;
+                }
+
+                steps.reverse();
+                return Some(steps);
+            }
+
+            expansions += 1;
+
+            if expansions > MAX_PATH_EXPANSIONS {
+                return None;
+            }
+
+            let cost = costs[&pos];
+
+            for (neighbor, step_cost, controls) in self.path_neighbors(pos, jump_height) {
+                let new_cost = cost + step_cost;
+
+                if costs
+                    .get(&neighbor)
+                    .is_some_and(|&old_cost| old_cost <= new_cost)
+                {
+                    continue;
+                }
+
+                costs.insert(neighbor, new_cost);
+                came_from.insert(neighbor, (pos, controls));
+                open.push(Reverse((new_cost + heuristic(neighbor), neighbor)));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::chunk::{Tile, CHUNK_WIDTH};
+
+    const FLOOR: Dim = 10;
+    const JUMP_HEIGHT: Dim = 2;
+
+    /// Empty chunks `0..chunks` with a stone floor at `FLOOR`, so walkers
+    /// stand at `FLOOR + 1`.
+    fn flat_terrain(chunks: Dim) -> Terrain {
+        let mut terrain = Terrain::empty(0..chunks);
+
+        for col in 0..chunks * CHUNK_WIDTH as Dim {
+            terrain[Coord { row: FLOOR, col }] = Some(Tile::Stone);
+        }
+
+        terrain
+    }
+
+    fn fill(terrain: &mut Terrain, rows: std::ops::RangeInclusive<Dim>, col: Dim) {
+        for row in rows {
+            terrain[Coord { row, col }] = Some(Tile::Stone);
+        }
+    }
+
+    fn ground(col: Dim) -> Coord {
+        Coord {
+            row: FLOOR + 1,
+            col,
+        }
+    }
+
+    #[test]
+    fn jumps_up_a_step_within_jump_height() {
+        let mut terrain = flat_terrain(2);
+
+        for col in 10..20 {
+            fill(&mut terrain, FLOOR + 1..=FLOOR + JUMP_HEIGHT, col);
+        }
+
+        let goal = ground(15)
+            + Coord {
+                row: JUMP_HEIGHT,
+                col: 0,
+            };
+        let path = terrain.find_path(ground(2), goal, JUMP_HEIGHT).unwrap();
+
+        assert_eq!(path.last().unwrap().target, goal);
+        assert!(path.iter().any(|step| step.controls.jump));
+    }
+
+    #[test]
+    fn does_not_jump_a_wall_taller_than_jump_height() {
+        let mut terrain = flat_terrain(2);
+        fill(&mut terrain, FLOOR + 1..=FLOOR + JUMP_HEIGHT + 1, 10);
+
+        assert!(terrain
+            .find_path(ground(2), ground(15), JUMP_HEIGHT)
+            .is_none());
+    }
+
+    #[test]
+    fn drops_off_a_ledge() {
+        let mut terrain = flat_terrain(2);
+        let ledge = FLOOR + MAX_PLANNED_DROP;
+
+        for col in 0..6 {
+            fill(&mut terrain, FLOOR + 1..=ledge, col);
+        }
+
+        let start = Coord {
+            row: ledge + 1,
+            col: 2,
+        };
+
+        let path = terrain.find_path(start, ground(15), JUMP_HEIGHT).unwrap();
+
+        assert_eq!(path.last().unwrap().target, ground(15));
+        assert!(path.iter().any(|step| step.target == ground(6)));
+        assert!(path.iter().all(|step| !step.controls.jump));
+    }
+
+    #[test]
+    fn dives_down_through_water() {
+        let mut terrain = flat_terrain(2);
+
+        for row in FLOOR + 1..=FLOOR + 4 {
+            terrain[Coord { row, col: 5 }] = Some(Tile::Water);
+        }
+
+        let start = ground(5) + Coord { row: 3, col: 0 };
+        let path = terrain.find_path(start, ground(5), JUMP_HEIGHT).unwrap();
+
+        assert_eq!(path.len(), 3);
+        assert!(path.iter().all(|step| step.controls.down));
+    }
+
+    #[test]
+    fn gives_up_on_unreachable_goals() {
+        // Wide enough that the search runs into `MAX_PATH_EXPANSIONS` before
+        // it runs out of floor.
+        let chunks = 2 * MAX_PATH_EXPANSIONS as Dim / CHUNK_WIDTH as Dim;
+        let mut terrain = flat_terrain(chunks);
+
+        // Wall the goal in.
+        let goal = ground(chunks * CHUNK_WIDTH as Dim / 2);
+
+        for col in [goal.col - 1, goal.col + 1] {
+            fill(&mut terrain, FLOOR + 1..=FLOOR + 3, col);
+        }
+
+        terrain[goal + Coord::UP] = Some(Tile::Stone);
+
+        assert!(terrain.find_path(ground(0), goal, JUMP_HEIGHT).is_none());
+    }
+}
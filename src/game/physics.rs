@@ -1,8 +1,11 @@
-use crate::util::{Coord, DimReal};
+use crate::util::{approach, Coord, DimReal};
 
 use super::{
+    camera::LANDING_SHAKE,
     chunk::Tile,
-    entity::{Entity, MovementMode},
+    components::{Body, EntityFlags, JumpState, MovementControls, MovementMode, WalkingParams},
+    console::{no_completions, parse_arg_in, Command, Console},
+    health::{fall_damage, DamageEvent, DamageSource},
     terrain::Terrain,
     Game,
 };
@@ -11,96 +14,369 @@ pub const TIMESTEP: DimReal = 1.0 / 60.0;
 
 pub const BREAKING_FORCE: DimReal = 60000.0;
 
+/// Strongest gravity, either way, the `gravity` command accepts.
+pub const MAX_GRAVITY: DimReal = 100.0;
+
 impl Game {
+    /// Integrates every entity with a `Body` and a `MovementMode`.
     pub fn update_physics_bodies(&mut self) {
         let gravity_accel = self.gravity * TIMESTEP;
 
-        for entity in &mut self.entities {
-            match entity.movement_mode {
+        let entities = &mut self.entities;
+
+        for (id, body) in entities.bodies.iter_mut() {
+            let Some(mode) = entities.movement_modes.get(id) else {
+                continue;
+            };
+
+            // Entities outside the loaded world wait for their chunk to come back.
+            if !self.terrain.is_column_loaded(body.tile_pos().col) {
+                continue;
+            }
+
+            let controls = entities.movement_controls.get(id).copied();
+            let controls = controls.unwrap_or(MovementControls::EMPTY);
+
+            let mut fallback_flags = EntityFlags::empty();
+            let flags = entities.flags.get_mut(id).unwrap_or(&mut fallback_flags);
+
+            let fluid = self.terrain[body.tile_pos()].and_then(|tile| tile.fluid());
+            flags.set(EntityFlags::IN_FLUID, fluid.is_some());
+
+            match mode {
                 MovementMode::Static => continue,
                 MovementMode::Dynamic => (),
-                MovementMode::Walking {
-                    walking_speed,
-                    jump_impulse,
-                } => {
-                    let walk_direction = entity.movement_controls.horizontal_direction();
-
-                    entity.velocity.col = {
-                        if walk_direction == 0.0 {
-                            0.0
-                        } else {
-                            let vel = entity.velocity.col + walking_speed * walk_direction;
-                            vel.clamp(-walking_speed, walking_speed)
-                        }
-                    };
-
-                    if entity.movement_controls.jump && entity.on_ground {
-                        entity.velocity.row += jump_impulse;
-                    }
+                MovementMode::Walking(params) => {
+                    let mut fallback = JumpState::default();
+                    let jump = entities.jump_states.get_mut(id).unwrap_or(&mut fallback);
+
+                    body.walk(params, &controls, jump, flags, &self.terrain);
                 }
             }
 
-            entity.velocity += gravity_accel * entity.gravity_scale;
-            entity.position += entity.velocity * TIMESTEP;
+            if !flags.contains(EntityFlags::CLIMBING) {
+                body.velocity += gravity_accel * body.gravity_scale;
+            }
+
+            if let Some(fluid) = fluid {
+                body.velocity += gravity_accel * -fluid.buoyancy;
+                body.velocity = body.velocity * (1.0 - fluid.drag * TIMESTEP).max(0.0);
+
+                if fluid.contact_damage > 0.0 {
+                    self.damage_events.push(DamageEvent {
+                        target: id,
+                        amount: fluid.contact_damage,
+                        source: DamageSource::Fluid,
+                    });
+                }
+            }
+
+            body.position += body.velocity * TIMESTEP;
         }
     }
 
     pub fn solve_collisions(&mut self) {
-        let mut entities = self.entities.clone();
+        let entities = &mut self.entities;
+
+        // Tiles broken by fast moving entities. They count as empty for the
+        // rest of the pass and are destroyed at the end of it.
+        let mut broken = vec![];
+
+        for (id, body) in entities.bodies.iter_mut() {
+            let Some(flags) = entities.flags.get_mut(id) else {
+                continue;
+            };
+
+            let was_on_ground = flags.contains(EntityFlags::ON_GROUND);
+            flags.remove(EntityFlags::CONTACTS);
+
+            if flags.contains(EntityFlags::NO_CLIP) {
+                continue;
+            }
 
-        for entity in &mut entities {
-            entity.on_ground = false;
-            entity.collide(&mut self.terrain);
+            let drop_through = match entities.movement_controls.get(id) {
+                Some(controls) => controls.down,
+                None => false,
+            };
+
+            let impact_speed = -body.velocity.row;
+
+            *flags |= body.collide(&self.terrain, &mut broken, drop_through);
+
+            if flags.contains(EntityFlags::ON_GROUND) && !was_on_ground {
+                let amount = fall_damage(impact_speed);
+
+                if amount > 0.0 {
+                    self.damage_events.push(DamageEvent {
+                        target: id,
+                        amount,
+                        source: DamageSource::Fall,
+                    });
+
+                    if flags.contains(EntityFlags::PLAYER) {
+                        self.camera_shake.add_trauma(amount * LANDING_SHAKE);
+                    }
+                }
+            }
         }
 
-        self.entities = entities;
+        for pos in broken {
+            self.destroy_tile(pos);
+        }
     }
 }
 
 fn tile_impassable(terrain: &Terrain, pos: Coord) -> bool {
-    match &terrain[pos] {
-        Some(tile) if tile.is_impassable() => true,
-        _ => false,
-    }
+    matches!(&terrain[pos], Some(tile) if tile.is_impassable())
+}
+
+/// Whether the tile stops an entity, skipping tiles broken earlier in the pass.
+fn tile_blocks(terrain: &Terrain, broken: &[Coord], pos: Coord) -> bool {
+    tile_impassable(terrain, pos) && !broken.contains(&pos)
+}
+
+fn tile_one_way(terrain: &Terrain, pos: Coord) -> bool {
+    matches!(&terrain[pos], Some(tile) if tile.is_one_way())
+}
+
+fn tile_climbable(terrain: &Terrain, pos: Coord) -> bool {
+    matches!(&terrain[pos], Some(tile) if tile.is_climbable())
 }
 
-impl Entity {
-    fn collide(&mut self, terrain: &mut Terrain) {
-        self.process_horizontal_collision(terrain);
-        self.process_vertical_collision(terrain);
+/// Whether an entity can stand on the tile.
+fn tile_supports(terrain: &Terrain, pos: Coord, drop_through: bool) -> bool {
+    tile_impassable(terrain, pos) || (!drop_through && tile_one_way(terrain, pos))
+}
+
+impl Body {
+    fn walk(
+        &mut self,
+        params: &WalkingParams,
+        controls: &MovementControls,
+        jump: &mut JumpState,
+        flags: &mut EntityFlags,
+        terrain: &Terrain,
+    ) {
+        let on_ground = flags.contains(EntityFlags::ON_GROUND);
+
+        self.update_climbing(params, controls, flags, terrain);
+        let climbing = flags.contains(EntityFlags::CLIMBING);
+
+        let on_platform = tile_one_way(terrain, self.tile_below());
+        let crouching = on_ground && controls.down && !on_platform;
+
+        let control = if on_ground || climbing {
+            1.0
+        } else {
+            params.air_control
+        };
+
+        let max_speed = if crouching {
+            params.max_speed * params.crouch_speed_factor
+        } else {
+            params.max_speed
+        };
+
+        let walk_direction = controls.horizontal_direction();
+
+        if walk_direction != 0.0 {
+            flags.set(EntityFlags::FACING_LEFT, walk_direction < 0.0);
+        }
+
+        let (target, rate) = {
+            if walk_direction != 0.0 {
+                (walk_direction * max_speed, params.acceleration)
+            } else if on_ground || climbing {
+                let friction = match &terrain[self.tile_below()] {
+                    Some(tile) => tile.friction(),
+                    None => 1.0,
+                };
+
+                (0.0, params.deceleration * friction)
+            } else {
+                (0.0, params.deceleration)
+            }
+        };
+
+        self.velocity.col = approach(self.velocity.col, target, rate * control * TIMESTEP);
+
+        if flags.contains(EntityFlags::IN_FLUID) {
+            self.swim(params, controls, jump);
+        } else {
+            self.update_jump(params, controls, jump, on_ground || climbing);
+        }
+
+        // Bumping a ceiling ends the jump, so letting go of jump afterwards
+        // doesn't cut the fall.
+        if flags.contains(EntityFlags::ON_CEILING) {
+            jump.rising = false;
+        }
+
+        if jump.rising {
+            flags.remove(EntityFlags::CLIMBING);
+        }
+    }
+
+    fn swim(&mut self, params: &WalkingParams, controls: &MovementControls, jump: &mut JumpState) {
+        jump.jump_held = controls.jump;
+        jump.buffer_timer = 0.0;
+        jump.rising = false;
+
+        // Jump swims up and down dives, both against the fluid's buoyancy.
+        let target = match (controls.jump, controls.down) {
+            (true, false) => params.swim_speed,
+            (false, true) => -params.swim_speed,
+            _ => return,
+        };
+
+        let accel = params.acceleration * TIMESTEP;
+        self.velocity.row = approach(self.velocity.row, target, accel);
+    }
+
+    /// Grab onto climbable tiles with up/down and move along them without gravity.
+    fn update_climbing(
+        &mut self,
+        params: &WalkingParams,
+        controls: &MovementControls,
+        flags: &mut EntityFlags,
+        terrain: &Terrain,
+    ) {
+        let vertical_direction = controls.vertical_direction();
+
+        if !tile_climbable(terrain, self.tile_pos()) {
+            flags.remove(EntityFlags::CLIMBING);
+            return;
+        }
+
+        if vertical_direction != 0.0 {
+            flags.insert(EntityFlags::CLIMBING);
+        }
+
+        if flags.contains(EntityFlags::CLIMBING) {
+            self.velocity.row = vertical_direction * params.climb_speed;
+        }
+    }
+
+    fn update_jump(
+        &mut self,
+        params: &WalkingParams,
+        controls: &MovementControls,
+        jump: &mut JumpState,
+        on_ground: bool,
+    ) {
+        // Holding jump through a landing mustn't count as a new press.
+        let pressed = controls.jump && !jump.jump_held;
+        jump.jump_held = controls.jump;
+
+        if on_ground {
+            jump.coyote_timer = params.coyote_time;
+        } else {
+            jump.coyote_timer = (jump.coyote_timer - TIMESTEP).max(0.0);
+        }
+
+        if pressed {
+            jump.buffer_timer = params.jump_buffer_time;
+        } else {
+            jump.buffer_timer = (jump.buffer_timer - TIMESTEP).max(0.0);
+        }
+
+        if jump.buffer_timer > 0.0 && jump.coyote_timer > 0.0 {
+            // Set rather than add, so a jump can't stack on top of another.
+            self.velocity.row = params.jump_impulse;
+
+            jump.buffer_timer = 0.0;
+            jump.coyote_timer = 0.0;
+            jump.rising = true;
+        } else if self.velocity.row <= 0.0 {
+            jump.rising = false;
+        } else if jump.rising && !controls.jump {
+            self.velocity.row *= params.jump_cut;
+            jump.rising = false;
+        }
+    }
+
+    /// Returns the contact flags gained while resolving the collision.
+    ///
+    /// One-way platforms are skipped when `drop_through` is set.
+    /// Tiles the entity breaks through are added to `broken`.
+    fn collide(
+        &mut self,
+        terrain: &Terrain,
+        broken: &mut Vec<Coord>,
+        drop_through: bool,
+    ) -> EntityFlags {
+        self.process_horizontal_collision(terrain, broken)
+            | self.process_vertical_collision(terrain, broken, drop_through)
     }
 
-    fn process_horizontal_collision(&mut self, terrain: &mut Terrain) {
+    fn process_horizontal_collision(
+        &mut self,
+        terrain: &Terrain,
+        broken: &mut Vec<Coord>,
+    ) -> EntityFlags {
         let eps = 0.01;
         let pos = self.tile_pos();
 
-        if tile_impassable(terrain, pos) && self.velocity.col.abs() > eps {
-            self.process_collision(terrain, false);
+        if tile_blocks(terrain, broken, pos) && self.velocity.col.abs() > eps {
+            self.process_collision(terrain, broken, false);
+            EntityFlags::TOUCHING_WALL
+        } else {
+            EntityFlags::empty()
         }
     }
 
-    fn process_vertical_collision(&mut self, terrain: &mut Terrain) {
+    fn process_vertical_collision(
+        &mut self,
+        terrain: &Terrain,
+        broken: &mut Vec<Coord>,
+        drop_through: bool,
+    ) -> EntityFlags {
         let eps = 0.01;
         let pos = self.tile_pos();
 
-        if !tile_impassable(terrain, pos) {
-            return;
-        }
+        // Platforms only stop entities that were above them on the previous tick.
+        let landing_on_platform = {
+            let previous_row = self.position.row - self.velocity.row * TIMESTEP;
+
+            !drop_through
+                && self.velocity.row < -eps
+                && previous_row >= pos.row as DimReal + 0.5 - eps
+                && tile_one_way(terrain, pos)
+        };
 
-        if self.velocity.row < -eps {
-            self.on_ground = true;
+        if !tile_blocks(terrain, broken, pos) && !landing_on_platform {
+            // Resting entities sink into the ground a little before colliding
+            // again, so check for a supporting tile as well.
+            let below = self.tile_below();
+
+            if self.velocity.row <= eps && tile_supports(terrain, below, drop_through) {
+                return EntityFlags::ON_GROUND;
+            }
+
+            return EntityFlags::empty();
         }
 
+        let contacts = {
+            if self.velocity.row < -eps {
+                EntityFlags::ON_GROUND
+            } else if self.velocity.row > eps {
+                EntityFlags::ON_CEILING
+            } else {
+                EntityFlags::empty()
+            }
+        };
+
         if self.velocity.row.abs() > eps {
-            self.process_collision(terrain, true);
+            self.process_collision(terrain, broken, true);
         }
+
+        contacts
     }
 
-    fn process_collision(&mut self, terrain: &mut Terrain, vertical: bool) {
+    fn process_collision(&mut self, terrain: &Terrain, broken: &mut Vec<Coord>, vertical: bool) {
         let pos = self.tile_pos();
 
-        let tile_ref = &mut terrain[pos];
-        let tile = tile_ref.unwrap();
+        let tile = terrain[pos].unwrap();
 
         let breaking_velocity = self.breaking_velocity_of(&tile);
 
@@ -117,8 +393,7 @@ impl Entity {
         if velocity_axis.abs() > breaking_velocity {
             *velocity_axis -= direction * breaking_velocity;
 
-            // TODO: add a tile destruction procedure.
-            *tile_ref = None;
+            broken.push(pos);
         } else {
             if direction > 0.0 {
                 *position_axis = position_axis.floor();
@@ -130,8 +405,25 @@ impl Entity {
         }
     }
 
-    fn breaking_velocity_of(&self, _tile: &Tile) -> DimReal {
-        // TODO: add tile durability.
-        BREAKING_FORCE * TIMESTEP / self.mass
+    fn breaking_velocity_of(&self, tile: &Tile) -> DimReal {
+        BREAKING_FORCE * tile.durability() * TIMESTEP / self.mass
     }
 }
+
+pub fn register_commands(console: &mut Console) {
+    console.register(Command {
+        name: "gravity",
+        usage: "gravity [value]",
+        run: |game, args| {
+            if args.is_empty() {
+                return Ok(format!("Gravity: {}", -game.gravity.row));
+            }
+
+            let value = parse_arg_in(args, 0, "value", -MAX_GRAVITY..=MAX_GRAVITY)?;
+            game.gravity.row = -value;
+
+            Ok(format!("Gravity set to {value}"))
+        },
+        complete: no_completions,
+    });
+}
@@ -1,67 +1,56 @@
 use crossterm::style::Color;
 
-use crate::{
-    game::{anim::Animation, renderer::Pixel},
-    util::DimReal,
-};
+use crate::{game::renderer::Pixel, util::Coord};
 
 use super::{
-    chunk::TERRAIN_HEIGHT,
-    entity::{Entity, EntityFlag, MovementMode},
+    components::{Body, EntityFlags, Health, MovementMode, Sprite, WalkingParams},
+    entity::{EntityBuilder, EntityId},
+    inventory::{Inventory, PLAYER_INVENTORY_SIZE},
     Game,
 };
 
-pub fn new_player() -> Entity {
-    let animations = {
-        let basic = Animation::new(vec![Pixel {
-            character: '@',
-            fg_color: Color::Grey,
-            bg_color: None,
-        }]);
-
-        vec![basic]
-    };
-
-    let mut player = Entity::new(animations);
-
-    player.position.row = (TERRAIN_HEIGHT + 1) as DimReal;
-
-    player.mass = 50.0;
-
-    player.movement_mode = MovementMode::Walking {
-        walking_speed: 8.0,
-        jump_impulse: 10.0,
-    };
-
-    player.flags.insert(EntityFlag::Player);
-
-    player
+pub fn new_player(position: Coord) -> EntityBuilder {
+    let sprite = Sprite::still(Pixel {
+        character: '@',
+        fg_color: Color::Grey,
+        bg_color: None,
+    });
+
+    let mut body = Body::new(position.to_real());
+
+    body.mass = 50.0;
+
+    EntityBuilder::new()
+        .with_body(body)
+        .with_sprite(sprite)
+        .with_movement_mode(MovementMode::Walking(WalkingParams::default()))
+        .with_health(Health::new(100.0))
+        // TODO: load the inventory once saving is implemented.
+        .with_inventory(Inventory::new(PLAYER_INVENTORY_SIZE))
+        .with_flags(EntityFlags::PLAYER)
 }
 
 impl Game {
-    pub fn find_player(&self) -> Option<usize> {
-        for (idx, entity) in self.entities.iter().enumerate() {
-            if entity.flags.contains(&EntityFlag::Player) {
-                return Some(idx);
-            }
-        }
-
-        None
+    pub fn find_player(&self) -> Option<EntityId> {
+        self.entities
+            .ids()
+            .find(|id| self.entities.has_flags(*id, EntityFlags::PLAYER))
     }
 
-    pub fn get_player(&self) -> Option<&Entity> {
-        let idx = self.find_player()?;
-        Some(&self.entities[idx])
+    pub fn get_player_body(&self) -> Option<&Body> {
+        let id = self.find_player()?;
+        self.entities.bodies.get(id)
     }
 
-    pub fn get_player_mut(&mut self) -> Option<&mut Entity> {
-        let idx = self.find_player()?;
-        Some(&mut self.entities[idx])
+    pub fn get_player_body_mut(&mut self) -> Option<&mut Body> {
+        let id = self.find_player()?;
+        self.entities.bodies.get_mut(id)
     }
 
     pub fn snap_camera_to_player(&mut self) {
-        if let Some(player) = self.get_player() {
-            self.camera.position = player.tile_pos();
+        if let Some(position) = self.get_player_body().map(|body| body.position) {
+            self.camera_controller.snap(position);
+            self.camera.position = self.camera_controller.tile_focus();
         }
     }
 }
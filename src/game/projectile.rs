@@ -0,0 +1,255 @@
+use crossterm::style::Color;
+
+use crate::util::{Coord, CoordReal, Dim, DimReal};
+
+use super::{
+    components::{Body, EntityFlags, MovementMode, Projectile, ProjectileEffect, Sprite},
+    entity::{EntityBuilder, EntityId},
+    explosion::Explosion,
+    health::DamageSource,
+    physics::TIMESTEP,
+    renderer::Pixel,
+    Game,
+};
+
+/// Distance between samples when tracing a projectile's path through a tick.
+const TRACE_STEP: DimReal = 0.25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectileKind {
+    Arrow,
+    MiningCharge,
+    Bomb,
+}
+
+impl ProjectileKind {
+    pub fn speed(&self) -> DimReal {
+        match self {
+            Self::Arrow => 30.0,
+            Self::MiningCharge => 15.0,
+            Self::Bomb => 12.0,
+        }
+    }
+}
+
+pub fn new_projectile(
+    kind: ProjectileKind,
+    owner: Option<EntityId>,
+    position: CoordReal,
+    velocity: CoordReal,
+) -> EntityBuilder {
+    let (character, color, gravity_scale, lifetime, effect) = match kind {
+        ProjectileKind::Arrow => ('-', Color::White, 0.5, 10.0, ProjectileEffect::Stick(15.0)),
+        ProjectileKind::MiningCharge => ('o', Color::Red, 1.0, 5.0, ProjectileEffect::BreakTile),
+        ProjectileKind::Bomb => (
+            '@',
+            Color::DarkGrey,
+            1.0,
+            5.0,
+            ProjectileEffect::Explode {
+                radius: 3.0,
+                power: 3.0,
+            },
+        ),
+    };
+
+    let mut body = Body::new(position);
+
+    body.velocity = velocity;
+    body.mass = 0.1;
+    body.gravity_scale = gravity_scale;
+
+    EntityBuilder::new()
+        .with_body(body)
+        .with_sprite(Sprite::still(Pixel {
+            character,
+            fg_color: color,
+            bg_color: None,
+        }))
+        .with_movement_mode(MovementMode::Dynamic)
+        .with_projectile(Projectile {
+            owner,
+            lifetime,
+            effect,
+            stuck: false,
+        })
+        .with_flags(EntityFlags::NO_CLIP)
+}
+
+enum Hit {
+    Tile(Coord),
+    Entity(EntityId),
+}
+
+impl Game {
+    pub fn launch_projectile(
+        &mut self,
+        kind: ProjectileKind,
+        owner: Option<EntityId>,
+        position: CoordReal,
+        direction: CoordReal,
+    ) -> EntityId {
+        let length = direction.length();
+
+        let direction = if length > 0.0 {
+            direction * (1.0 / length)
+        } else {
+            CoordReal { row: 0.0, col: 1.0 }
+        };
+
+        let velocity = direction * kind.speed();
+        self.spawn(new_projectile(kind, owner, position, velocity))
+    }
+
+    /// Throw a projectile from the player in the direction they're facing.
+    pub fn fire_player_projectile(&mut self, kind: ProjectileKind) {
+        let Some(player) = self.find_player() else {
+            return;
+        };
+
+        let Some(body) = self.entities.bodies.get(player) else {
+            return;
+        };
+
+        let facing_left = self.entities.has_flags(player, EntityFlags::FACING_LEFT);
+
+        let direction = CoordReal {
+            row: 0.1,
+            col: if facing_left { -1.0 } else { 1.0 },
+        };
+
+        let position = body.position;
+        self.launch_projectile(kind, Some(player), position, direction);
+    }
+
+    /// Age projectiles and resolve what they hit during the last physics step.
+    pub fn update_projectiles(&mut self) {
+        let mut flying = vec![];
+        let mut expired = vec![];
+
+        for (id, projectile) in self.entities.projectiles.iter_mut() {
+            projectile.lifetime -= TIMESTEP;
+
+            if projectile.lifetime <= 0.0 {
+                expired.push(id);
+            } else if !projectile.stuck {
+                flying.push((id, *projectile));
+            }
+        }
+
+        let mut hits = vec![];
+
+        for (id, projectile) in flying {
+            let Some(body) = self.entities.bodies.get(id) else {
+                continue;
+            };
+
+            if !self.terrain.contains(body.tile_pos()) {
+                expired.push(id);
+                continue;
+            }
+
+            if let Some(hit) = self.trace_projectile(id, projectile.owner, body) {
+                hits.push((id, projectile, hit));
+            }
+        }
+
+        for id in expired {
+            self.entities.mark_for_deletion(id);
+        }
+
+        for (id, projectile, hit) in hits {
+            self.apply_projectile_hit(id, projectile, hit);
+        }
+    }
+
+    /// Walk the segment covered by the projectile this tick and report the first hit.
+    fn trace_projectile(&self, id: EntityId, owner: Option<EntityId>, body: &Body) -> Option<Hit> {
+        let travelled = body.velocity * TIMESTEP;
+        let start = body.position - travelled;
+
+        let samples = (travelled.length() / TRACE_STEP).ceil().max(1.0) as Dim;
+
+        for sample in 1..=samples {
+            let point = start + travelled * (sample as DimReal / samples as DimReal);
+
+            let tile_pos = Coord {
+                row: point.row.round() as Dim,
+                col: point.col.round() as Dim,
+            };
+
+            let target = self.entities.healths.ids().find(|&target| {
+                let hittable = Some(target) != owner && target != id;
+
+                hittable
+                    && match self.entities.bodies.get(target) {
+                        Some(target_body) => target_body.tile_pos() == tile_pos,
+                        None => false,
+                    }
+            });
+
+            if let Some(target) = target {
+                return Some(Hit::Entity(target));
+            }
+
+            if matches!(self.terrain[tile_pos], Some(tile) if tile.is_impassable()) {
+                return Some(Hit::Tile(tile_pos));
+            }
+        }
+
+        None
+    }
+
+    fn apply_projectile_hit(&mut self, id: EntityId, projectile: Projectile, hit: Hit) {
+        let source = DamageSource::Projectile {
+            owner: projectile.owner,
+        };
+
+        // Explosive tiles go off when hit by anything.
+        if let Hit::Tile(pos) = hit {
+            if self.terrain[pos].is_some_and(|tile| tile.explosion(pos).is_some()) {
+                self.entities.mark_for_deletion(id);
+                self.destroy_tile(pos);
+                return;
+            }
+        }
+
+        match (projectile.effect, hit) {
+            (ProjectileEffect::Stick(amount), Hit::Entity(target)) => {
+                self.deal_damage(target, amount, source);
+                self.entities.mark_for_deletion(id);
+            }
+            (ProjectileEffect::BreakTile, Hit::Tile(pos)) => {
+                self.destroy_tile(pos);
+                self.entities.mark_for_deletion(id);
+            }
+            (ProjectileEffect::Stick(_), Hit::Tile(_)) => {
+                if let Some(projectile) = self.entities.projectiles.get_mut(id) {
+                    projectile.stuck = true;
+                }
+
+                if let Some(mode) = self.entities.movement_modes.get_mut(id) {
+                    *mode = MovementMode::Static;
+                }
+
+                if let Some(body) = self.entities.bodies.get_mut(id) {
+                    body.velocity = CoordReal::ZERO;
+                }
+            }
+            (ProjectileEffect::Explode { radius, power }, hit) => {
+                self.entities.mark_for_deletion(id);
+
+                let center = match hit {
+                    Hit::Tile(pos) => pos.to_real(),
+                    Hit::Entity(target) => match self.entities.bodies.get(target) {
+                        Some(body) => body.position,
+                        None => return,
+                    },
+                };
+
+                self.explode(Explosion::new(center, radius, power));
+            }
+            _ => self.entities.mark_for_deletion(id),
+        }
+    }
+}
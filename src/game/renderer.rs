@@ -13,15 +13,34 @@ use crate::util::{Coord, Dim};
 
 pub struct Camera {
     pub position: Coord,
+    /// World tiles per screen cell along each axis. Above 1 the terrain is
+    /// drawn as a downsampled overview.
+    pub zoom: Dim,
 }
 
 impl Camera {
     pub fn project(&self, world_point: Coord, screen: &Screen) -> Coord {
         Coord {
-            row: screen.rows() / 2 + world_point.row - self.position.row,
-            col: screen.cols() / 2 + world_point.col - self.position.col,
+            row: screen.rows() / 2 + (world_point.row - self.position.row).div_euclid(self.zoom),
+            col: screen.cols() / 2 + (world_point.col - self.position.col).div_euclid(self.zoom),
         }
     }
+
+    /// Inverse of `project`. Returns the bottom left tile covered by the cell
+    /// when zoomed out.
+    pub fn unproject(&self, screen_point: Coord, screen: &Screen) -> Coord {
+        Coord {
+            row: (screen_point.row - screen.rows() / 2) * self.zoom + self.position.row,
+            col: (screen_point.col - screen.cols() / 2) * self.zoom + self.position.col,
+        }
+    }
+
+    pub fn is_visible(&self, world_point: Coord, screen: &Screen) -> bool {
+        let screen_coord = self.project(world_point, screen);
+
+        (0..screen.rows()).contains(&screen_coord.row)
+            && (0..screen.cols()).contains(&screen_coord.col)
+    }
 }
 
 pub type OverwriteFn = fn(&mut Pixel, &Pixel);
@@ -66,7 +85,7 @@ impl Pixel {
             return;
         }
 
-        overwrite_fn(&mut screen[screen_coord], &self);
+        overwrite_fn(&mut screen[screen_coord], self);
     }
 }
 
@@ -78,6 +97,8 @@ impl Default for Pixel {
 
 pub struct Screen {
     buffer: Vec<Pixel>,
+    /// Drawn over `buffer` in `end_frame`. Text goes here so the world never covers it.
+    overlay: Vec<Option<Pixel>>,
     rows: Dim,
     cols: Dim,
 }
@@ -86,6 +107,7 @@ impl Screen {
     pub fn new() -> Self {
         Self {
             buffer: vec![],
+            overlay: vec![],
             rows: 0,
             cols: 0,
         }
@@ -115,12 +137,22 @@ impl Screen {
         if old_len < new_len {
             self.clear();
         } else if old_len > new_len {
-            self.buffer = self.buffer.iter().take(new_len as usize).cloned().collect();
+            self.buffer.truncate(new_len as usize);
+            self.overlay.truncate(new_len as usize);
         }
     }
 
     pub fn clear(&mut self) {
         self.buffer = vec![Default::default(); self.len() as usize];
+        self.overlay = vec![None; self.len() as usize];
+    }
+
+    /// Convert a terminal cell to screen coordinates, undoing the flip in `end_frame`.
+    pub fn terminal_to_screen(&self, terminal_point: Coord) -> Coord {
+        Coord {
+            row: self.rows() - 1 - terminal_point.row,
+            col: terminal_point.col,
+        }
     }
 
     pub fn rows(&self) -> Dim {
@@ -131,6 +163,7 @@ impl Screen {
         self.cols
     }
 
+    /// Print text on the overlay layer.
     pub fn print_string(&mut self, position: Coord, text: &str) {
         for (col, character) in text.chars().enumerate() {
             let coord = Coord {
@@ -146,11 +179,13 @@ impl Screen {
                 return;
             }
 
-            self[coord] = Pixel {
+            let index = self.flat_index(coord);
+
+            self.overlay[index] = Some(Pixel {
                 character,
                 fg_color: Color::Grey,
                 bg_color: Some(Color::Black),
-            };
+            });
         }
     }
 
@@ -197,7 +232,12 @@ impl Screen {
             let row = self.rows() - 1 - row;
 
             for col in 0..self.cols() {
-                let pixel = &self[Coord { row, col }];
+                let coord = Coord { row, col };
+
+                let pixel = match &self.overlay[self.flat_index(coord)] {
+                    Some(pixel) => pixel,
+                    None => &self[coord],
+                };
 
                 if pixel.fg_color != last_fg_color {
                     stdout.queue(SetForegroundColor(pixel.fg_color))?;
@@ -227,13 +267,13 @@ impl Index<Coord> for Screen {
 
     fn index(&self, index: Coord) -> &Self::Output {
         let index = self.flat_index(index);
-        &self.buffer[index as usize]
+        &self.buffer[index]
     }
 }
 
 impl IndexMut<Coord> for Screen {
     fn index_mut(&mut self, index: Coord) -> &mut Self::Output {
         let index = self.flat_index(index);
-        &mut self.buffer[index as usize]
+        &mut self.buffer[index]
     }
 }
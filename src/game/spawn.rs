@@ -0,0 +1,116 @@
+use crate::util::{Coord, CoordReal, Dim};
+
+use super::{
+    chunk::{Tile, CHUNK_WIDTH, TERRAIN_HEIGHT},
+    components::{EntityFlags, JumpState},
+    entity::EntityId,
+    player::new_player,
+    Game,
+};
+
+/// How many columns to each side of the requested one are tried when
+/// looking for dry land to spawn on.
+pub const SPAWN_SEARCH_RADIUS: Dim = 128;
+
+impl Game {
+    /// Find a spot on dry land closest to `near_col`, loading chunks as needed.
+    pub fn find_spawn_point(&mut self, near_col: Dim) -> Coord {
+        let center_chunk = near_col.div_euclid(CHUNK_WIDTH as Dim);
+        self.terrain.load_chunks_around(center_chunk);
+
+        for offset in 0..=SPAWN_SEARCH_RADIUS {
+            for col in [near_col + offset, near_col - offset] {
+                if let Some(row) = self.terrain.dry_surface_height(col) {
+                    return Coord { row: row + 1, col };
+                }
+            }
+        }
+
+        Coord {
+            row: TERRAIN_HEIGHT as Dim + 1,
+            col: near_col,
+        }
+    }
+
+    /// Spawn the player at the saved spawn point, or find one if there's none yet.
+    pub fn spawn_player(&mut self) -> EntityId {
+        let spawn_point = match self.spawn_point {
+            Some(spawn_point) => spawn_point,
+            None => self.find_spawn_point(0),
+        };
+
+        self.spawn_point = Some(spawn_point);
+        self.spawn(new_player(spawn_point))
+    }
+
+    /// Bring a dead player back to life at the spawn point.
+    pub fn respawn_player(&mut self) {
+        let Some(player) = self.find_player() else {
+            self.spawn_player();
+            return;
+        };
+
+        let spawn_point = self.respawn_point();
+        self.spawn_point = Some(spawn_point);
+
+        if let Some(body) = self.entities.bodies.get_mut(player) {
+            body.position = spawn_point.to_real();
+            body.velocity = CoordReal::ZERO;
+        }
+
+        if let Some(health) = self.entities.healths.get_mut(player) {
+            health.current = health.max;
+            health.invulnerability_timer = 0.0;
+            health.last_damage = None;
+        }
+
+        if let Some(jump) = self.entities.jump_states.get_mut(player) {
+            *jump = JumpState::default();
+        }
+
+        if let Some(flags) = self.entities.flags.get_mut(player) {
+            flags.remove(EntityFlags::DEAD | EntityFlags::CONTACTS);
+        }
+
+        self.snap_camera_to_player();
+        self.load_chunks_around_camera();
+    }
+
+    /// The saved spawn point if it's a checkpoint that still stands, otherwise
+    /// the closest dry land to it.
+    fn respawn_point(&mut self) -> Coord {
+        let Some(spawn_point) = self.spawn_point else {
+            return self.find_spawn_point(0);
+        };
+
+        let center_chunk = spawn_point.col.div_euclid(CHUNK_WIDTH as Dim);
+        self.terrain.load_chunks_around(center_chunk);
+
+        if self.terrain[spawn_point] == Some(Tile::Checkpoint) {
+            return spawn_point;
+        }
+
+        self.find_spawn_point(spawn_point.col)
+    }
+
+    /// Save the player's position when they touch a checkpoint.
+    pub fn update_spawn_point(&mut self) {
+        let Some(player) = self.find_player() else {
+            return;
+        };
+
+        if self.entities.has_flags(player, EntityFlags::DEAD) {
+            return;
+        }
+
+        let Some(body) = self.entities.bodies.get(player) else {
+            return;
+        };
+
+        let pos = body.tile_pos();
+
+        if self.terrain[pos] == Some(Tile::Checkpoint) {
+            self.spawn_point = Some(pos);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::ops::RangeInclusive;
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::util::{Coord, Dim, DimReal};
+
+use super::{
+    biome::Biome,
+    components::EntityFlags,
+    mob::{new_mob, MobKind},
+    renderer::Screen,
+    time::MAX_LIGHT,
+    Game,
+};
+
+/// Spawning is attempted once every this many ticks.
+pub const SPAWN_INTERVAL: u64 = 30;
+
+/// Candidate positions tried per spawning attempt.
+pub const SPAWN_ATTEMPTS: usize = 4;
+
+/// Mobs spawn within this many columns past half a screen's width from the player.
+pub const SPAWN_BAND_WIDTH: Dim = 32;
+
+/// Mobs this many columns past the spawning band are removed.
+pub const DESPAWN_MARGIN: Dim = 32;
+
+pub const PASSIVE_MOB_CAP: usize = 8;
+pub const HOSTILE_MOB_CAP: usize = 6;
+
+pub struct SpawnRule {
+    pub kind: MobKind,
+    pub biomes: &'static [Biome],
+    /// Rows below the surface, where 0 is standing right on top of it.
+    pub depth: RangeInclusive<Dim>,
+    /// Brightest light the mob is willing to spawn in.
+    pub max_light: u8,
+}
+
+pub const SPAWN_RULES: &[SpawnRule] = &[
+    SpawnRule {
+        kind: MobKind::Sheep,
+        biomes: &[Biome::Plains, Biome::Mountains],
+        depth: 0..=0,
+        max_light: MAX_LIGHT,
+    },
+    SpawnRule {
+        kind: MobKind::Zombie,
+        biomes: &[Biome::Beach, Biome::Plains, Biome::Mountains],
+        depth: 0..=Dim::MAX,
+        max_light: 7,
+    },
+];
+
+impl Game {
+    /// Populate loaded chunks near the player, out of the camera's sight.
+    /// Measured from the player, like `despawn_far_mobs`, so mobs spawned while
+    /// the camera is detached aren't removed right away.
+    pub fn spawn_mobs(&mut self, screen: &Screen) {
+        if !self.tick_count.is_multiple_of(SPAWN_INTERVAL) {
+            return;
+        }
+
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..SPAWN_ATTEMPTS {
+            let Some((pos, depth)) = self.pick_spawn_position(screen, &mut rng) else {
+                continue;
+            };
+
+            let biome = self.terrain.biome_at(pos.col);
+            let light = self.light_level(pos);
+
+            let candidates: Vec<_> = SPAWN_RULES
+                .iter()
+                .filter(|rule| rule.biomes.contains(&biome))
+                .filter(|rule| rule.depth.contains(&depth))
+                .filter(|rule| light <= rule.max_light)
+                .filter(|rule| !self.mob_cap_reached(rule.kind.is_hostile()))
+                .collect();
+
+            if let Some(rule) = candidates.choose(&mut rng) {
+                self.spawn(new_mob(rule.kind, pos));
+            }
+        }
+    }
+
+    /// A random standable spot off-screen, along with its depth below the surface.
+    fn pick_spawn_position(&self, screen: &Screen, rng: &mut impl Rng) -> Option<(Coord, Dim)> {
+        let distance = screen.cols() / 2 + rng.gen_range(1..=SPAWN_BAND_WIDTH);
+        let side = if rng.gen() { 1 } else { -1 };
+
+        let center = self.get_player_body()?.tile_pos().col;
+        let col = center + distance * side;
+        let surface = self.terrain.dry_surface_height(col)? + 1;
+
+        // Half of the attempts go to caves.
+        let row = if rng.gen() {
+            surface
+        } else {
+            rng.gen_range(0..surface)
+        };
+
+        let pos = Coord { row, col };
+
+        if !self.terrain.can_stand(pos) || self.camera.is_visible(pos, screen) {
+            return None;
+        }
+
+        Some((pos, surface - row))
+    }
+
+    fn mob_cap_reached(&self, hostile: bool) -> bool {
+        let count = self
+            .entities
+            .ais
+            .ids()
+            .filter(|&id| self.entities.has_flags(id, EntityFlags::HOSTILE) == hostile)
+            .count();
+
+        let cap = if hostile {
+            HOSTILE_MOB_CAP
+        } else {
+            PASSIVE_MOB_CAP
+        };
+
+        count >= cap
+    }
+
+    /// Remove mobs that wandered too far from the player or out of the loaded world.
+    pub fn despawn_far_mobs(&mut self, screen: &Screen) {
+        let player_position = self.get_player_body().map(|body| body.position);
+        let despawn_distance = screen.cols() / 2 + SPAWN_BAND_WIDTH + DESPAWN_MARGIN;
+
+        let mut far = vec![];
+
+        for id in self.entities.ais.ids() {
+            if self.entities.has_flags(id, EntityFlags::PERSISTENT) {
+                continue;
+            }
+
+            let Some(body) = self.entities.bodies.get(id) else {
+                continue;
+            };
+
+            let unloaded = !self.terrain.contains(body.tile_pos());
+
+            let too_far = match player_position {
+                Some(position) => {
+                    (position.col - body.position.col).abs() > despawn_distance as DimReal
+                }
+                None => false,
+            };
+
+            if unloaded || too_far {
+                far.push(id);
+            }
+        }
+
+        for id in far {
+            self.entities.mark_for_deletion(id);
+        }
+    }
+}
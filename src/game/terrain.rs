@@ -1,11 +1,16 @@
-use std::ops::{Index, IndexMut};
+use std::{
+    collections::BTreeSet,
+    ops::{Index, IndexMut},
+};
 
-use noise::OpenSimplex;
+use noise::{OpenSimplex, Seedable};
 
 use crate::util::{Coord, Dim};
 
 use super::{
-    chunk::{Chunk, Tile, CHUNK_WIDTH},
+    biome::Biome,
+    chunk::{generated_height, Chunk, Tile, CHUNK_HEIGHT, CHUNK_WIDTH},
+    console::{parse_arg, Command, Console},
     Game,
 };
 
@@ -14,6 +19,10 @@ pub const CHUNKS_LOADED_RADIUS: Dim = 16;
 pub struct Terrain {
     noise: OpenSimplex,
     loaded_chunks: Vec<Chunk>,
+    /// Fluid cells that may be able to move. See `fluid.rs`.
+    pub(super) active_fluids: BTreeSet<Coord>,
+    /// Tiles that may have lost their support. See `falling.rs`.
+    pub(super) falling_candidates: BTreeSet<Coord>,
 }
 
 impl Terrain {
@@ -24,16 +33,116 @@ impl Terrain {
         Self {
             noise: OpenSimplex::new(seed),
             loaded_chunks: vec![],
+            active_fluids: BTreeSet::new(),
+            falling_candidates: BTreeSet::new(),
         }
     }
 
+    /// Terrain made of empty, loaded chunks, for building worlds by hand.
+    #[cfg(test)]
+    pub fn empty(chunks: std::ops::Range<Dim>) -> Self {
+        let mut terrain = Self::new();
+
+        terrain.loaded_chunks = chunks
+            .map(|pos| Chunk::new(pos * CHUNK_WIDTH as Dim))
+            .collect();
+
+        terrain
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.noise.seed()
+    }
+
     pub fn loaded_chunks(&self) -> &[Chunk] {
         &self.loaded_chunks
     }
+
+    /// Biome of a world column, derived from its generated (not current) terrain.
+    pub fn biome_at(&self, col: Dim) -> Biome {
+        Biome::from_height(generated_height(&self.noise, col))
+    }
+
+    /// Row of the topmost impassable tile in the column, or `None` if the
+    /// column isn't loaded or is topped by a fluid.
+    pub fn dry_surface_height(&self, col: Dim) -> Option<Dim> {
+        for row in (0..CHUNK_HEIGHT as Dim).rev() {
+            let pos = Coord { row, col };
+
+            if !self.contains(pos) {
+                return None;
+            }
+
+            match self[pos] {
+                Some(tile) if tile.fluid().is_some() => return None,
+                Some(tile) if tile.is_impassable() => return Some(row),
+                _ => (),
+            }
+        }
+
+        None
+    }
+
+    /// Whether the coordinate lies inside a loaded chunk.
+    pub fn contains(&self, pos: Coord) -> bool {
+        self.loaded_chunks.iter().any(|chunk| chunk.contains(pos))
+    }
+
+    /// Whether the chunk holding the column is loaded, regardless of row.
+    pub fn is_column_loaded(&self, col: Dim) -> bool {
+        self.contains(Coord { row: 0, col })
+    }
+
+    /// Change a tile and wake up any fluids or falling blocks around it.
+    ///
+    /// Prefer this to `IndexMut` for gameplay changes to the terrain.
+    pub fn set_tile(&mut self, pos: Coord, tile: Option<Tile>) {
+        if !self.contains(pos) {
+            return;
+        }
+
+        self[pos] = tile;
+        self.activate_fluids_around(pos);
+
+        self.falling_candidates.insert(pos);
+        self.falling_candidates.insert(pos + Coord::UP);
+    }
+
+    pub fn activate_fluids_around(&mut self, pos: Coord) {
+        for row in -1..=1 {
+            for col in -1..=1 {
+                self.active_fluids.insert(pos + Coord { row, col });
+            }
+        }
+    }
+
+    /// Wake up fluids along both sides of a chunk's edges, so they can flow
+    /// across the seams between freshly loaded chunks.
+    fn activate_chunk_seams(&mut self, chunk_world_position: Dim) {
+        let columns = [
+            chunk_world_position - 1,
+            chunk_world_position,
+            chunk_world_position + CHUNK_WIDTH as Dim - 1,
+            chunk_world_position + CHUNK_WIDTH as Dim,
+        ];
+
+        for col in columns {
+            for row in 0..CHUNK_HEIGHT {
+                let pos = Coord {
+                    row: row as Dim,
+                    col,
+                };
+
+                if matches!(self[pos], Some(tile) if tile.fluid().is_some()) {
+                    self.active_fluids.insert(pos);
+                }
+            }
+        }
+    }
 }
 
 impl Terrain {
-    fn load_chunks_around(&mut self, center_chunk: Dim) {
+    pub(super) fn load_chunks_around(&mut self, center_chunk: Dim) {
         let start = center_chunk - CHUNKS_LOADED_RADIUS;
         let end = center_chunk + CHUNKS_LOADED_RADIUS;
 
@@ -66,7 +175,8 @@ impl Terrain {
             // TODO: load chunk if available.
             new_chunk.regenerate(&self.noise);
 
-            self.loaded_chunks.push(new_chunk)
+            self.loaded_chunks.push(new_chunk);
+            self.activate_chunk_seams(pos * CHUNK_WIDTH as Dim);
         }
     }
 }
@@ -109,8 +219,9 @@ impl IndexMut<Coord> for Terrain {
             }
         }
 
-        // TODO: write an error message for `expect`.
-        chunk.map(|chunk| &mut chunk[index]).unwrap()
+        chunk
+            .map(|chunk| &mut chunk[index])
+            .expect("Chunk not loaded; check it with `contains` before `index_mut`")
     }
 }
 
@@ -120,3 +231,38 @@ impl Game {
         self.terrain.load_chunks_around(center_chunk);
     }
 }
+
+pub fn register_commands(console: &mut Console) {
+    console.register(Command {
+        name: "setblock",
+        usage: "setblock <col> <row> <tile|air>",
+        run: |game, args| {
+            let col: Dim = parse_arg(args, 0, "col")?;
+            let row: Dim = parse_arg(args, 1, "row")?;
+            let id = args.get(2).ok_or("Missing <tile>")?;
+
+            let tile = match *id {
+                "air" => None,
+                id => Some(Tile::from_id(id).ok_or_else(|| format!("Unknown tile `{id}`"))?),
+            };
+
+            let pos = Coord { row, col };
+
+            if !game.terrain.contains(pos) {
+                return Err(format!("{col} {row} is not loaded"));
+            }
+
+            game.terrain.set_tile(pos, tile);
+            Ok(format!("Set {col} {row} to {id}"))
+        },
+        complete: |idx| match idx {
+            2 => Tile::NAMED
+                .iter()
+                .map(|tile| tile.id())
+                .chain(["air"])
+                .map(String::from)
+                .collect(),
+            _ => vec![],
+        },
+    });
+}
@@ -0,0 +1,85 @@
+use crate::util::{Coord, Dim};
+
+use super::{
+    chunk::CHUNK_HEIGHT,
+    console::{parse_arg, Command, Console},
+    Game,
+};
+
+/// Length of a full day/night cycle in ticks.
+pub const DAY_LENGTH: u64 = 60 * 60 * 10;
+
+/// Brightest possible light level.
+pub const MAX_LIGHT: u8 = 15;
+
+/// Sky light at midnight.
+pub const NIGHT_LIGHT: u8 = 4;
+
+impl Game {
+    /// Ticks since the start of the current day.
+    pub fn time_of_day(&self) -> u64 {
+        self.time % DAY_LENGTH
+    }
+
+    pub fn set_time_of_day(&mut self, time_of_day: u64) {
+        self.time = self.time - self.time_of_day() + time_of_day % DAY_LENGTH;
+    }
+
+    pub fn is_night(&self) -> bool {
+        self.time_of_day() >= DAY_LENGTH / 2
+    }
+
+    pub fn advance_time(&mut self) {
+        self.time += 1;
+    }
+
+    pub fn sky_light(&self) -> u8 {
+        if self.is_night() {
+            NIGHT_LIGHT
+        } else {
+            MAX_LIGHT
+        }
+    }
+
+    /// Light at a tile. Only sky light exists so far: tiles with open sky
+    /// above them get it in full, everything else is dark.
+    pub fn light_level(&self, pos: Coord) -> u8 {
+        let top = (pos.row + 1)..(CHUNK_HEIGHT as Dim);
+
+        let covered = top
+            .map(|row| Coord { row, col: pos.col })
+            .any(|pos| matches!(self.terrain[pos], Some(tile) if tile.is_impassable()));
+
+        if covered {
+            0
+        } else {
+            self.sky_light()
+        }
+    }
+}
+
+pub fn register_commands(console: &mut Console) {
+    console.register(Command {
+        name: "time",
+        usage: "time [set <day|night|ticks>]",
+        run: |game, args| {
+            match args {
+                [] => {}
+                ["set", "day"] => game.set_time_of_day(0),
+                ["set", "night"] => game.set_time_of_day(DAY_LENGTH / 2),
+                ["set", _] => {
+                    let ticks: u64 = parse_arg(args, 1, "ticks")?;
+                    game.set_time_of_day(ticks);
+                }
+                _ => return Err(String::from("Invalid arguments")),
+            }
+
+            Ok(format!("Time of day: {}/{DAY_LENGTH}", game.time_of_day()))
+        },
+        complete: |idx| match idx {
+            0 => vec![String::from("set")],
+            1 => vec![String::from("day"), String::from("night")],
+            _ => vec![],
+        },
+    });
+}
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+
+use crate::util::DimReal;
+
+use super::chunk::Tile;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ToolKind {
+    Pickaxe,
+    Shovel,
+    Axe,
+}
+
+impl ToolKind {
+    pub const ALL: [Self; 3] = [Self::Pickaxe, Self::Shovel, Self::Axe];
+}
+
+/// Better tiers mine faster, last longer and can mine harder tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ToolTier {
+    Stone,
+    Iron,
+}
+
+impl ToolTier {
+    pub const ALL: [Self; 2] = [Self::Stone, Self::Iron];
+
+    /// Mining speed multiplier on tiles the tool is meant for.
+    pub fn speed(&self) -> DimReal {
+        match self {
+            Self::Stone => 3.0,
+            Self::Iron => 5.0,
+        }
+    }
+
+    /// Tiles a fresh tool can mine before breaking.
+    pub fn max_durability(&self) -> u32 {
+        match self {
+            Self::Stone => 64,
+            Self::Iron => 192,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tool {
+    pub kind: ToolKind,
+    pub tier: ToolTier,
+    /// Uses left before the tool breaks.
+    pub durability: u32,
+}
+
+impl Tool {
+    pub fn new(kind: ToolKind, tier: ToolTier) -> Self {
+        Self {
+            kind,
+            tier,
+            durability: tier.max_durability(),
+        }
+    }
+
+    pub fn all() -> impl Iterator<Item = Self> {
+        ToolTier::ALL.into_iter().flat_map(|tier| {
+            ToolKind::ALL
+                .into_iter()
+                .map(move |kind| Self::new(kind, tier))
+        })
+    }
+
+    pub fn id(&self) -> &'static str {
+        use ToolKind::*;
+        use ToolTier::*;
+
+        match (self.tier, self.kind) {
+            (Stone, Pickaxe) => "stone_pickaxe",
+            (Stone, Shovel) => "stone_shovel",
+            (Stone, Axe) => "stone_axe",
+            (Iron, Pickaxe) => "iron_pickaxe",
+            (Iron, Shovel) => "iron_shovel",
+            (Iron, Axe) => "iron_axe",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::all().find(|tool| tool.id() == id)
+    }
+
+    pub fn name(&self) -> &'static str {
+        use ToolKind::*;
+        use ToolTier::*;
+
+        match (self.tier, self.kind) {
+            (Stone, Pickaxe) => "Stone pickaxe",
+            (Stone, Shovel) => "Stone shovel",
+            (Stone, Axe) => "Stone axe",
+            (Iron, Pickaxe) => "Iron pickaxe",
+            (Iron, Shovel) => "Iron shovel",
+            (Iron, Axe) => "Iron axe",
+        }
+    }
+
+    /// Whether the tool is the right kind and tier for the tile.
+    pub fn is_suited_for(&self, tile: &Tile) -> bool {
+        tile.preferred_tool() == Some(self.kind)
+            && tile.required_tier().is_none_or(|tier| self.tier >= tier)
+    }
+}
+
+impl Tile {
+    pub fn preferred_tool(&self) -> Option<ToolKind> {
+        use Tile::*;
+
+        match self {
+            Stone | IronOre | Checkpoint => Some(ToolKind::Pickaxe),
+            Dirt | GrassyDirt | Sand | Gravel => Some(ToolKind::Shovel),
+            Ladder | Platform | Workbench => Some(ToolKind::Axe),
+            _ => None,
+        }
+    }
+
+    /// Lowest tier of the preferred tool that can mine the tile at all.
+    pub fn required_tier(&self) -> Option<ToolTier> {
+        match self {
+            Tile::IronOre => Some(ToolTier::Stone),
+            _ => None,
+        }
+    }
+}
@@ -2,6 +2,7 @@ use super::Game;
 
 impl Game {
     pub fn update_entities_state(&mut self) {
-        // TODO: implement.
+        self.update_ai();
+        self.perform_mob_attacks();
     }
 }
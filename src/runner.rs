@@ -2,9 +2,10 @@ use std::time::{Duration, Instant};
 
 use crossterm::event::{self};
 
+#[allow(unused_imports)]
 pub use crossterm::event::Event as CrosstermEvent;
 
-use crate::game::{player::new_player, renderer::Screen, Game};
+use crate::game::{renderer::Screen, Game};
 
 pub const REFRESH_RATE: f32 = 60.0;
 pub const REFRESH_DELAY_SECONDS: f32 = 1.0 / REFRESH_RATE;
@@ -12,8 +13,7 @@ pub const REFRESH_DELAY_SECONDS: f32 = 1.0 / REFRESH_RATE;
 pub fn crossterm_runner() -> crossterm::Result<()> {
     let mut game = Game::new();
 
-    let player = new_player();
-    game.spawn(player);
+    game.spawn_player();
 
     let mut screen = Screen::new();
 
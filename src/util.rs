@@ -3,7 +3,16 @@ use std::ops::{Add, AddAssign, Mul, Sub};
 pub type Dim = i64;
 pub type DimReal = f32;
 
-#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+/// Move `current` towards `target` by at most `max_delta`, without overshooting.
+pub fn approach(current: DimReal, target: DimReal, max_delta: DimReal) -> DimReal {
+    if current < target {
+        (current + max_delta).min(target)
+    } else {
+        (current - max_delta).max(target)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Coord {
     pub row: Dim,
     pub col: Dim,
@@ -11,6 +20,10 @@ pub struct Coord {
 
 impl Coord {
     pub const ZERO: Self = Self { row: 0, col: 0 };
+    pub const UP: Self = Self { row: 1, col: 0 };
+    pub const DOWN: Self = Self { row: -1, col: 0 };
+    pub const LEFT: Self = Self { row: 0, col: -1 };
+    pub const RIGHT: Self = Self { row: 0, col: 1 };
 
     pub fn to_real(self) -> CoordReal {
         CoordReal {
@@ -31,7 +44,18 @@ impl Add for Coord {
     }
 }
 
-#[derive(Clone, Copy, Default, PartialEq)]
+impl Sub for Coord {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::Output {
+            row: self.row - rhs.row,
+            col: self.col - rhs.col,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub struct CoordReal {
     pub row: DimReal,
     pub col: DimReal,
@@ -39,6 +63,10 @@ pub struct CoordReal {
 
 impl CoordReal {
     pub const ZERO: Self = Self { row: 0.0, col: 0.0 };
+
+    pub fn length(self) -> DimReal {
+        (self.row * self.row + self.col * self.col).sqrt()
+    }
 }
 
 impl Add for CoordReal {